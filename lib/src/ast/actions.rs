@@ -1,6 +1,9 @@
 use std::fmt;
 
-use crate::{lexical::tokens::token::Token, syntactic::parsing_table::Production};
+use crate::{
+    lexical::tokens::{token::Token, token_type::Type},
+    syntactic::parsing_table::Production,
+};
 
 use super::{
     nodes::{CodeNode, NodeValue, StructNode},
@@ -79,6 +82,7 @@ where
                 }
             }
 
+            fit_to_children(&subtree, token);
             stack.push(subtree);
         },
     )
@@ -108,6 +112,7 @@ where
                 }
             }
 
+            fit_to_children(&subtree, token);
             stack.push(subtree);
         },
     )
@@ -123,3 +128,32 @@ pub fn create_marker() -> SemanticAction {
         },
     )
 }
+
+/**
+ * Point a subtree at its first child's token and make it span from its first to its last child,
+ * since the token current when the action runs is usually whatever came *after* the subtree
+ *
+ * Calls are the exception, their action runs right after their closing `)`, which isn't a node but
+ * is still part of the call (and the only end `f()` has, since its argument list is empty)
+ */
+fn fit_to_children(subtree: &CodeNode, token: &Token) {
+    let (first, last) = match (subtree.first_child(), subtree.last_child()) {
+        (Some(first), Some(last)) => (first, last),
+        // Empty subtrees (ie. an empty IndiceList) keep the current token
+        _ => return,
+    };
+
+    let first_token = first.borrow().token.clone();
+    let mut span = first.borrow().span.merge(&last.borrow().span);
+
+    let mut subtree_ref = subtree.borrow_mut();
+
+    if let (NodeValue::Tree(TreeNode::FunctionCall()), Type::ClosePar) =
+        (&subtree_ref.value, &token.token_type)
+    {
+        span = span.merge(&token.span());
+    }
+
+    subtree_ref.token = first_token;
+    subtree_ref.span = span;
+}
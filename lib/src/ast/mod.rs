@@ -1,3 +1,5 @@
 pub mod actions;
 pub mod nodes;
+pub mod query;
+pub mod symbol_table;
 pub mod tree_node;
@@ -1,7 +1,6 @@
 use std::{
     cell::RefCell,
-    collections::HashMap,
-    fmt::{Display, Formatter, Write},
+    fmt::{Display, Formatter},
     mem::discriminant,
     rc::Rc,
     sync::atomic::{AtomicUsize, Ordering},
@@ -11,10 +10,10 @@ use rctree::Node;
 
 use crate::{
     compiler_error::CompilerError,
-    lexical::tokens::{token::Token, token_type::Type},
+    lexical::tokens::{span::Span, token::Token, token_type::Type},
 };
 
-use super::tree_node::TreeNode;
+use super::{symbol_table::SymbolTable, tree_node::TreeNode};
 
 #[derive(Debug, Clone)]
 pub enum NodeValue {
@@ -43,19 +42,54 @@ impl Display for NodeValue {
 pub enum VarType {
     Integer(Vec<usize>),
     Float(Vec<usize>),
-    Class(String),
+    Class(String, Vec<usize>),
     Function,
     Void,
-    Global,
     IndiceList(usize),
-    ArgumentList(Vec<VarType>),
-    Inherits(Vec<String>),
+    /// The type of something that failed to check, its error is already reported
+    ///
+    /// It's compatible with everything, so whatever uses it doesn't report the same mistake again.
+    Error,
 }
 
 impl VarType {
     pub fn eq_variant(&self, other: &VarType) -> bool {
         discriminant(self) == discriminant(other)
     }
+
+    /// The dimensions of an integer, float or object array, empty if it's not an array
+    ///
+    /// Parameters declared like `arr: integer[]` have unsized dimensions, which are `0`
+    pub fn dimensions(&self) -> &[usize] {
+        match self {
+            VarType::Integer(dims) | VarType::Float(dims) | VarType::Class(_, dims) => dims,
+            _ => &[],
+        }
+    }
+
+    /// The type left after indexing `count` dimensions, "popping off" dimensions from the start
+    ///
+    /// So if `arr: integer[7][8][9]` then `arr[1]` is `Integer([8, 9])` and `arr[1][2]` is
+    /// `Integer([9])`. Returns None if there aren't `count` dimensions to index, except for an
+    /// `Error`, which can be indexed any number of times.
+    pub fn indexed(&self, count: usize) -> Option<VarType> {
+        if *self == VarType::Error {
+            return Some(VarType::Error);
+        }
+
+        if count > self.dimensions().len() {
+            return None;
+        }
+
+        match self {
+            VarType::Integer(dims) => Some(VarType::Integer(dims[count..].to_vec())),
+            VarType::Float(dims) => Some(VarType::Float(dims[count..].to_vec())),
+            VarType::Class(name, dims) => {
+                Some(VarType::Class(name.clone(), dims[count..].to_vec()))
+            }
+            _ => Some(self.clone()),
+        }
+    }
 }
 
 impl Display for VarType {
@@ -63,32 +97,11 @@ impl Display for VarType {
         match self {
             VarType::Integer(idx) => write!(f, "Integer{}", indexes_to_string(idx)),
             VarType::Float(idx) => write!(f, "Float{}", indexes_to_string(idx)),
-            VarType::Class(c) => write!(f, "Class({})", c),
+            VarType::Class(c, idx) => write!(f, "Class({}){}", c, indexes_to_string(idx)),
             VarType::Function => write!(f, "Function"),
             VarType::Void => write!(f, "Void"),
-            VarType::Global => write!(f, "Global"),
+            VarType::Error => write!(f, "Error"),
             VarType::IndiceList(i) => write!(f, "IndiceList({})", i),
-            VarType::ArgumentList(args) => {
-                write!(
-                    f,
-                    "ArgumentList({})",
-                    args.iter()
-                        .map(|v| v.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
-            }
-            VarType::Inherits(inherits) => {
-                write!(
-                    f,
-                    "Inherits({})",
-                    inherits
-                        .iter()
-                        .map(|v| v.to_string())
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
-            }
         }
     }
 }
@@ -101,144 +114,31 @@ fn indexes_to_string(indexes: &[usize]) -> String {
             "[{}]",
             indexes
                 .iter()
-                .map(|i| i.to_string())
+                .map(|i| match i {
+                    0 => String::new(),
+                    i => i.to_string(),
+                })
                 .collect::<Vec<_>>()
                 .join(", ")
         )
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Visibility {
-    Public,
-    Private,
-}
-
-#[derive(Debug, Clone)]
-pub struct SymbolData {
-    pub size: usize,
-    pub offset: isize,
-    pub label: Option<String>,
-    pub table: Option<SymbolTable>,
-    pub var_type: VarType,
-    pub visibility: Option<Visibility>,
-}
-
-impl SymbolData {
-    pub fn new(size: usize, offset: isize, var_type: VarType) -> Self {
-        Self {
-            size,
-            offset,
-            label: None,
-            table: None,
-            var_type,
-            visibility: None,
-        }
-    }
-
-    pub fn new_with_table(
-        size: usize,
-        offset: isize,
-        var_type: VarType,
-        table: SymbolTable,
-    ) -> Self {
-        Self {
-            size,
-            offset,
-            label: None,
-            table: Some(table),
-            var_type,
-            visibility: None,
-        }
-    }
-}
-
-impl Display for SymbolData {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "size: {}, offset: {}, type: {}{}",
-            self.size,
-            self.offset,
-            self.var_type,
-            if self.table.is_some() {
-                ", -> Symbol Table"
-            } else {
-                ""
-            }
-        )
-    }
-}
-
-pub type SymbolTable = HashMap<String, Rc<RefCell<SymbolData>>>;
-
-pub fn fmt_symbol_table(table: &SymbolTable) -> Result<String, std::fmt::Error> {
-    let longest_key = table
-        .keys()
-        .map(|s| s.len())
-        .max()
-        .unwrap_or(0)
-        .max(" Symbol ".len());
-
-    let longest_value = table
-        .values()
-        .map(|s| s.borrow().to_string().len())
-        .max()
-        .unwrap_or(0)
-        .max(" Data ".len());
-
-    let mut output = String::new();
-
-    writeln!(
-        output,
-        "| {:=^longest_key$} | {:=^longest_value$} |",
-        " Symbol ", " Data "
-    )?;
-
-    let mut other_tables: Vec<(String, SymbolTable)> = Vec::new();
-
-    let mut to_print = table.iter().collect::<Vec<_>>();
-
-    to_print.sort_by(
-        |(k1, v1), (k2, v2)| match v2.borrow().offset.cmp(&v1.borrow().offset) {
-            std::cmp::Ordering::Equal => k1.cmp(k2),
-            ord => ord,
-        },
-    );
-
-    for (key, value) in to_print {
-        writeln!(
-            output,
-            "| {:<longest_key$} | {:<longest_value$} |",
-            key,
-            value.borrow().to_string(),
-        )?;
-
-        if key != ".." && !key.starts_with("_in") {
-            if let Some(other) = &value.borrow().table {
-                other_tables.push((key.clone(), other.clone()));
-            }
-        }
-    }
-
-    for (key, other) in other_tables {
-        let other_out = fmt_symbol_table(&other)?;
-        write!(output, "\n{key}:\n{other_out}")?;
-    }
-
-    Ok(output)
-}
-
 #[derive(Debug)]
 pub struct StructNode {
     pub id: usize,
     pub value: NodeValue,
     pub token: Token,
+    /// For leaves the span of their token, for subtrees from their first to last child
+    pub span: Span,
     pub symbol_table: RefCell<Option<SymbolTable>>,
 
     pub label: Rc<RefCell<Option<String>>>,
     pub code: Rc<RefCell<Option<String>>>,
     pub var_type: Rc<RefCell<Option<VarType>>>,
+    /// For function calls (and objects declared with constructor arguments), the global table key
+    /// of the overload they resolved to
+    pub target: RefCell<Option<String>>,
 }
 
 static ID_COUNT: AtomicUsize = AtomicUsize::new(1);
@@ -254,11 +154,13 @@ impl StructNode {
         Self {
             id,
             value,
+            span: token.span(),
             token,
             symbol_table: RefCell::new(None),
             label: Rc::new(RefCell::new(None)),
             code: Rc::new(RefCell::new(None)),
             var_type: Rc::new(RefCell::new(None)),
+            target: RefCell::new(None),
         }
     }
 
@@ -0,0 +1,509 @@
+use std::{
+    fmt::{Display, Formatter},
+    iter::Peekable,
+    str::{Chars, FromStr},
+};
+
+use crate::lexical::tokens::{span::Span, token_type::Type};
+
+use super::{
+    nodes::{CodeNode, NodeValue},
+    tree_node::TreeNode,
+};
+
+/// A predicate over AST nodes, built either with the combinators or parsed from a selector
+///
+/// ```rs
+/// // All calls to `add` somewhere inside a while body
+/// Query::tree(TreeNode::FunctionCall())
+///     .named("add")
+///     .inside(Query::tree(TreeNode::WhileBlock()));
+/// // Same thing, as a selector
+/// "WhileBlock FunctionCall#add".parse::<Query>();
+///
+/// // Assignments whose left side is a NestedVar
+/// Query::tree(TreeNode::Assignment()).child(0, Query::tree(TreeNode::NestedVar()));
+/// "Assignment:has(> NestedVar:first-child)".parse::<Query>();
+/// ```
+#[derive(Debug, Clone)]
+pub enum Query {
+    /// Matches every node
+    Any,
+    /// Matches nodes by the name of their value, `FunctionCall` for trees or `id` for leaves
+    Kind(String),
+    /// Matches `Id(name)` leaves, or nodes whose first child is one (calls, declarations, ...)
+    Named(String),
+    /// Matches the child of its parent at this index, counting from 0 (unlike `:nth-child`, which
+    /// counts from 1 like CSS)
+    Index(usize),
+    /// Matches the last child of its parent
+    Last,
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    /// Some ancestor matches
+    Inside(Box<Query>),
+    /// The direct parent matches
+    ChildOf(Box<Query>),
+    /// Some descendant matches
+    Has(Box<Query>),
+    /// Some direct child matches
+    HasChild(Box<Query>),
+}
+
+/// A node found by a query, along with where it is in the source
+#[derive(Debug, Clone)]
+pub struct QueryMatch {
+    pub node: CodeNode,
+    pub span: Span,
+}
+
+impl Query {
+    pub fn any() -> Self {
+        Query::Any
+    }
+
+    pub fn tree(tree_node: TreeNode) -> Self {
+        Query::Kind(tree_node.to_string())
+    }
+
+    pub fn leaf(leaf: Type) -> Self {
+        Query::Kind(leaf.to_string())
+    }
+
+    pub fn named(self, name: &str) -> Self {
+        self.and(Query::Named(name.to_string()))
+    }
+
+    pub fn and(self, other: Query) -> Self {
+        Query::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: Query) -> Self {
+        Query::Or(Box::new(self), Box::new(other))
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Query::Not(Box::new(self))
+    }
+
+    /// Only match nodes that have an ancestor matching `ancestor`
+    pub fn inside(self, ancestor: Query) -> Self {
+        self.and(Query::Inside(Box::new(ancestor)))
+    }
+
+    /// Only match nodes whose parent matches `parent`
+    pub fn child_of(self, parent: Query) -> Self {
+        self.and(Query::ChildOf(Box::new(parent)))
+    }
+
+    /// Only match nodes that have a descendant matching `descendant`
+    pub fn has(self, descendant: Query) -> Self {
+        self.and(Query::Has(Box::new(descendant)))
+    }
+
+    /// Only match nodes that have a direct child matching `child`
+    pub fn has_child(self, child: Query) -> Self {
+        self.and(Query::HasChild(Box::new(child)))
+    }
+
+    /// Only match nodes whose `index`th child matches `child`
+    pub fn child(self, index: usize, child: Query) -> Self {
+        self.has_child(Query::Index(index).and(child))
+    }
+
+    pub fn matches(&self, node: &CodeNode) -> bool {
+        match self {
+            Query::Any => true,
+            Query::Kind(kind) => &node.borrow().value.to_string() == kind,
+            Query::Named(name) => {
+                is_id(node, name) || node.first_child().is_some_and(|c| is_id(&c, name))
+            }
+            Query::Index(index) => {
+                node.parent().is_some() && node.preceding_siblings().count() == index + 1
+            }
+            Query::Last => node.parent().is_some() && node.next_sibling().is_none(),
+            Query::And(a, b) => a.matches(node) && b.matches(node),
+            Query::Or(a, b) => a.matches(node) || b.matches(node),
+            Query::Not(q) => !q.matches(node),
+            Query::Inside(q) => node.ancestors().skip(1).any(|a| q.matches(&a)),
+            Query::ChildOf(q) => node.parent().is_some_and(|p| q.matches(&p)),
+            Query::Has(q) => node.descendants().skip(1).any(|d| q.matches(&d)),
+            Query::HasChild(q) => node.children().any(|c| q.matches(&c)),
+        }
+    }
+
+    /// Find every node under (and including) `root` matching the query, in source order
+    pub fn find(&self, root: &CodeNode) -> Vec<QueryMatch> {
+        root.descendants()
+            .filter(|node| self.matches(node))
+            .map(|node| {
+                let span = node.borrow().span.clone();
+                QueryMatch { node, span }
+            })
+            .collect()
+    }
+}
+
+fn is_id(node: &CodeNode, name: &str) -> bool {
+    match &node.borrow().value {
+        NodeValue::Leaf(Type::Id(id)) => id == name,
+        NodeValue::Leaf(Type::SelfT) => name == "self",
+        _ => false,
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Query::Any => write!(f, "*"),
+            Query::Kind(kind) => write!(f, "{kind}"),
+            Query::Named(name) => write!(f, "#{name}"),
+            Query::Index(index) => write!(f, ":nth-child({})", index + 1),
+            Query::Last => write!(f, ":last-child"),
+            Query::And(a, b) => write!(f, "{a}{b}"),
+            Query::Or(a, b) => write!(f, ":is({a}, {b})"),
+            Query::Not(q) => write!(f, ":not({q})"),
+            Query::Inside(q) => write!(f, ":inside({q})"),
+            Query::ChildOf(q) => write!(f, ":child-of({q})"),
+            Query::Has(q) => write!(f, ":has({q})"),
+            Query::HasChild(q) => write!(f, ":has(> {q})"),
+        }
+    }
+}
+
+/**
+ * Parses a selector, loosely modelled on CSS:
+ *
+ * ```text
+ * selector   := compound (combinator compound)*
+ * combinator := ' ' (descendant) | '>' (child)
+ * compound   := (Kind | '*')? ('#' name)? pseudo*
+ * pseudo     := ':first-child' | ':last-child' | ':nth-child(' n ')'
+ *             | ':has(' '>'? selector ')' | ':not(' selector ')' | ':is(' selector (',' selector)* ')'
+ * ```
+ *
+ * Like CSS, `:nth-child(n)` counts from 1, so `:nth-child(1)` is the same as `:first-child`
+ *
+ * Kinds are tree names like `FunctionCall` or leaf names like `id` or `intnum`
+ */
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars().peekable();
+        let query = parse_selector(&mut chars)?;
+
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => Ok(query),
+            Some(c) => Err(format!("Unexpected '{c}' in query '{s}'")),
+        }
+    }
+}
+
+type QueryChars<'a> = Peekable<Chars<'a>>;
+
+fn parse_selector(chars: &mut QueryChars) -> Result<Query, String> {
+    skip_whitespace(chars);
+    let mut query = parse_compound(chars)?;
+
+    loop {
+        let had_whitespace = skip_whitespace(chars);
+
+        match chars.peek() {
+            Some('>') => {
+                chars.next();
+                skip_whitespace(chars);
+                query = parse_compound(chars)?.child_of(query);
+            }
+            Some(c) if had_whitespace && (c.is_alphabetic() || "*#:".contains(*c)) => {
+                query = parse_compound(chars)?.inside(query);
+            }
+            _ => return Ok(query),
+        }
+    }
+}
+
+fn parse_compound(chars: &mut QueryChars) -> Result<Query, String> {
+    let mut query = match chars.peek() {
+        Some('*') => {
+            chars.next();
+            Query::any()
+        }
+        // `#name` or `:pseudo` on their own are the same as `*#name` or `*:pseudo`
+        Some('#') | Some(':') => Query::any(),
+        Some(c) if c.is_alphabetic() => {
+            let kind = parse_ident(chars);
+
+            if kind.starts_with(char::is_uppercase) {
+                // Catch typos early, since otherwise they'd just silently match nothing
+                Query::tree(kind.parse()?)
+            } else {
+                Query::Kind(kind)
+            }
+        }
+        Some(c) => return Err(format!("Expected a node kind or '*', found '{c}'")),
+        None => return Err("Expected a node kind or '*', found the end of the query".to_string()),
+    };
+
+    if chars.peek() == Some(&'#') {
+        chars.next();
+        let name = parse_ident(chars);
+
+        if name.is_empty() {
+            return Err("Expected a name after '#'".to_string());
+        }
+
+        query = query.named(&name);
+    }
+
+    while chars.peek() == Some(&':') {
+        chars.next();
+        let pseudo = parse_ident(chars);
+
+        query = match pseudo.as_str() {
+            "first-child" => query.and(Query::Index(0)),
+            "last-child" => query.and(Query::Last),
+            "nth-child" => {
+                let arg = parse_parens(chars, |chars| {
+                    let n = parse_ident(chars);
+                    n.parse::<usize>().ok().filter(|n| *n > 0).ok_or_else(|| {
+                        format!("Expected a number from 1 in ':nth-child', found '{n}'")
+                    })
+                })?;
+                query.and(Query::Index(arg - 1))
+            }
+            "has" => {
+                let (direct, inner) = parse_parens(chars, |chars| {
+                    skip_whitespace(chars);
+                    let direct = chars.next_if_eq(&'>').is_some();
+                    Ok((direct, parse_selector(chars)?))
+                })?;
+
+                if direct {
+                    query.has_child(inner)
+                } else {
+                    query.has(inner)
+                }
+            }
+            "not" => query.and(parse_parens(chars, parse_selector)?.not()),
+            "is" => {
+                let inner = parse_parens(chars, |chars| {
+                    let mut inner = parse_selector(chars)?;
+
+                    while chars.next_if_eq(&',').is_some() {
+                        inner = inner.or(parse_selector(chars)?);
+                    }
+
+                    Ok(inner)
+                })?;
+                query.and(inner)
+            }
+            _ => return Err(format!("Unknown pseudo-class ':{pseudo}'")),
+        };
+    }
+
+    Ok(query)
+}
+
+fn parse_parens<T, F>(chars: &mut QueryChars, inner: F) -> Result<T, String>
+where
+    F: FnOnce(&mut QueryChars) -> Result<T, String>,
+{
+    if chars.next_if_eq(&'(').is_none() {
+        return Err("Expected '('".to_string());
+    }
+
+    let value = inner(chars)?;
+    skip_whitespace(chars);
+
+    if chars.next_if_eq(&')').is_none() {
+        return Err("Expected ')'".to_string());
+    }
+
+    Ok(value)
+}
+
+fn parse_ident(chars: &mut QueryChars) -> String {
+    let mut ident = String::new();
+
+    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_' || *c == '-') {
+        ident.push(c);
+    }
+
+    ident
+}
+
+/// Returns true if any whitespace was skipped
+fn skip_whitespace(chars: &mut QueryChars) -> bool {
+    let mut skipped = false;
+
+    while chars.next_if(|c| c.is_whitespace()).is_some() {
+        skipped = true;
+    }
+
+    skipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexical::lexer::LexerScanner, syntactic::predictive_parser};
+
+    const PROGRAM: &str = "function add(a: integer, b: integer) => integer {
+  return (a + b);
+}
+
+function main() => void {
+  localvar x: integer;
+  x = add(1, 2);
+  while (x < 10) {
+    x = add(x, 1);
+  };
+  write(x);
+}
+";
+
+    fn parse_program() -> CodeNode {
+        let mut lexer = LexerScanner::new(PROGRAM);
+        let (_, _, ast_stack) = predictive_parser::parse(&mut lexer).expect("Program should parse");
+        ast_stack
+            .first()
+            .expect("Program should have an AST")
+            .clone()
+    }
+
+    fn find(selector: &str) -> Vec<String> {
+        let query: Query = selector.parse().expect("Selector should parse");
+
+        query
+            .find(&parse_program())
+            .iter()
+            .map(|m| {
+                format!(
+                    "{}@{}:{}",
+                    m.node.borrow().value,
+                    m.span.start.line,
+                    m.span.start.column
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_selectors() {
+        let cases = [
+            ("FunctionCall", "FunctionCall"),
+            ("id#x", "id#x"),
+            ("#x", "*#x"),
+            (
+                "WhileBlock FunctionCall#add",
+                "FunctionCall#add:inside(WhileBlock)",
+            ),
+            ("Assignment > Variable", "Variable:child-of(Assignment)"),
+            (
+                "Assignment :first-child",
+                "*:nth-child(1):inside(Assignment)",
+            ),
+            ("*:nth-child(2):last-child", "*:nth-child(2):last-child"),
+            (
+                "Function:has(> FunctionHead)",
+                "Function:has(> FunctionHead)",
+            ),
+            ("Function:has(WhileBlock)", "Function:has(WhileBlock)"),
+            (":not(Write)", "*:not(Write)"),
+            (":is(Read, Write)", "*:is(Read, Write)"),
+        ];
+
+        for (selector, expected) in cases {
+            let query: Query = selector.parse().expect(selector);
+            assert_eq!(query.to_string(), expected, "{selector}");
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_selectors() {
+        for selector in [
+            "",
+            "Functoin",
+            "FunctionCall:nope",
+            "*:nth-child(0)",
+            "*:nth-child(x)",
+            "Assignment >",
+            "FunctionCall#",
+            "Function:has(WhileBlock",
+            "FunctionCall)",
+        ] {
+            assert!(selector.parse::<Query>().is_err(), "{selector}");
+        }
+    }
+
+    #[test]
+    fn matches_kinds_and_names() {
+        assert_eq!(
+            find("FunctionCall"),
+            ["FunctionCall@7:7", "FunctionCall@9:9"]
+        );
+        assert_eq!(find("FunctionCall#add").len(), 2);
+        assert!(find("FunctionCall#main").is_empty());
+        assert_eq!(find("id#b"), ["id@1:26", "id@2:15"]);
+    }
+
+    #[test]
+    fn matches_combinators() {
+        assert_eq!(find("WhileBlock FunctionCall#add"), ["FunctionCall@9:9"]);
+        assert_eq!(find("Expr > FunctionCall").len(), 2);
+        // Both the assigned `x` and the argument in the loop
+        assert_eq!(find("WhileBlock Variable#x").len(), 2);
+        // The call is the assignment's Expr's child, not the assignment's
+        assert!(find("Assignment > FunctionCall").is_empty());
+        assert_eq!(find("Function WhileBlock Assignment > Variable#x").len(), 1);
+    }
+
+    #[test]
+    fn matches_pseudo_classes() {
+        let first = find("Assignment > :first-child");
+        assert_eq!(first, ["Variable@7:3", "Variable@9:5"]);
+        assert_eq!(find("Assignment > :nth-child(1)"), first);
+
+        assert_eq!(
+            find("Assignment > :nth-child(2)"),
+            find("Assignment > :last-child")
+        );
+        assert!(find("Assignment > :nth-child(3)").is_empty());
+
+        assert_eq!(find("Function:has(WhileBlock)").len(), 1);
+        assert_eq!(find("FunctionCall:not(WhileBlock *)"), ["FunctionCall@7:7"]);
+        assert_eq!(find(":is(Read, Write, While)").len(), 2);
+    }
+
+    #[test]
+    fn combinators_match_selectors() {
+        let root = parse_program();
+        let spans = |query: &Query| {
+            query
+                .find(&root)
+                .into_iter()
+                .map(|m| m.span)
+                .collect::<Vec<_>>()
+        };
+
+        let built = Query::tree(TreeNode::FunctionCall())
+            .named("add")
+            .inside(Query::tree(TreeNode::WhileBlock()));
+        let parsed: Query = "WhileBlock FunctionCall#add".parse().unwrap();
+        assert_eq!(spans(&built), spans(&parsed));
+
+        let built = Query::tree(TreeNode::Assignment()).child(
+            1,
+            Query::tree(TreeNode::Expr()).has(Query::tree(TreeNode::FunctionCall())),
+        );
+        let parsed: Query = "Assignment:has(> Expr:nth-child(2):has(FunctionCall))"
+            .parse()
+            .unwrap();
+        assert_eq!(spans(&built).len(), 2);
+        assert_eq!(spans(&built), spans(&parsed));
+    }
+}
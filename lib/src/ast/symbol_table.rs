@@ -0,0 +1,482 @@
+use std::{
+    cell::{Ref, RefCell},
+    collections::{hash_map, HashMap},
+    fmt::{Display, Formatter, Write},
+    ops::Index,
+    rc::Rc,
+};
+
+use crate::lexical::tokens::location::Location;
+
+use super::nodes::VarType;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Private,
+}
+
+/// What a symbol was declared as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Class,
+    /// Free functions, member functions and constructors
+    Function,
+    /// Attributes of a class, inherited ones are only in the class that declares them
+    Attribute,
+    /// Parameters of a function, including the `self` pointer of member functions
+    Parameter,
+    /// Variables declared with `localvar`
+    Local,
+}
+
+impl Display for SymbolKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SymbolKind::Class => "class",
+            SymbolKind::Function => "function",
+            SymbolKind::Attribute => "attribute",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Local => "local",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SymbolData {
+    pub kind: SymbolKind,
+    pub size: usize,
+    pub offset: isize,
+    pub label: Option<String>,
+    pub table: Option<SymbolTable>,
+    pub var_type: VarType,
+    pub visibility: Option<Visibility>,
+    /// Where the symbol's name is declared, `None` for symbols the compiler adds itself
+    pub location: Option<Location>,
+    /// For member functions and constructors declared in a class, their parameter types, so calls
+    /// can be checked against the declaration even if there's no definition
+    pub params: Vec<VarType>,
+}
+
+impl SymbolData {
+    pub fn new(kind: SymbolKind, size: usize, offset: isize, var_type: VarType) -> Self {
+        Self {
+            kind,
+            size,
+            offset,
+            label: None,
+            table: None,
+            var_type,
+            visibility: None,
+            location: None,
+            params: vec![],
+        }
+    }
+
+    pub fn new_with_table(
+        kind: SymbolKind,
+        size: usize,
+        offset: isize,
+        var_type: VarType,
+        table: SymbolTable,
+    ) -> Self {
+        Self {
+            kind,
+            size,
+            offset,
+            label: None,
+            table: Some(table),
+            var_type,
+            visibility: None,
+            location: None,
+            params: vec![],
+        }
+    }
+}
+
+impl Display for SymbolData {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}, size: {}, offset: {}, type: {}{}",
+            self.kind,
+            self.size,
+            self.offset,
+            self.var_type,
+            if self.table.is_some() {
+                ", -> Symbol Table"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+/// What a symbol table is the scope of
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum ScopeKind {
+    #[default]
+    Global,
+    /// A class, by its name
+    Class(String),
+    /// A function, by its global table key (ie. `A::f(Integer)`)
+    Function(String),
+}
+
+impl ScopeKind {
+    /// How the scope is named in exports: the class name, the function's global table key, or
+    /// nothing for the global scope
+    pub fn name(&self) -> &str {
+        match self {
+            ScopeKind::Global => "",
+            ScopeKind::Class(name) | ScopeKind::Function(name) => name,
+        }
+    }
+
+    fn kind_name(&self) -> &'static str {
+        match self {
+            ScopeKind::Global => "global",
+            ScopeKind::Class(_) => "class",
+            ScopeKind::Function(_) => "function",
+        }
+    }
+}
+
+/// The symbols declared in a scope, along with what the scope itself is
+///
+/// Scopes nest like the program does: functions are in their class (for member functions) or the
+/// global scope, and classes are in the global scope. Tables on single declarations (like a
+/// `Parameter`) only carry symbols until they're merged into the scope they belong to.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    pub scope: ScopeKind,
+    /// The scope names are looked up in when they aren't declared here, `None` for the global
+    /// scope
+    pub parent: Option<ScopeKind>,
+    /// For function scopes, what the function returns (`Void` for constructors)
+    pub return_type: Option<VarType>,
+    /// For function scopes, the parameter types in order, for overload resolution
+    pub params: Vec<VarType>,
+    /// For class scopes, the classes it inherits from in `isa` order
+    pub bases: Vec<String>,
+    symbols: HashMap<String, Rc<RefCell<SymbolData>>>,
+}
+
+impl SymbolTable {
+    pub fn new(scope: ScopeKind, parent: Option<ScopeKind>) -> Self {
+        Self {
+            scope,
+            parent,
+            ..Default::default()
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Rc<RefCell<SymbolData>>> {
+        self.symbols.get(name)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.symbols.contains_key(name)
+    }
+
+    pub fn insert(
+        &mut self,
+        name: String,
+        data: Rc<RefCell<SymbolData>>,
+    ) -> Option<Rc<RefCell<SymbolData>>> {
+        self.symbols.insert(name, data)
+    }
+
+    /// Add every symbol of `other`, but not what its scope is
+    pub fn extend(&mut self, other: SymbolTable) {
+        self.symbols.extend(other.symbols);
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, String, Rc<RefCell<SymbolData>>> {
+        self.symbols.iter()
+    }
+
+    pub fn keys(&self) -> hash_map::Keys<'_, String, Rc<RefCell<SymbolData>>> {
+        self.symbols.keys()
+    }
+
+    pub fn values(&self) -> hash_map::Values<'_, String, Rc<RefCell<SymbolData>>> {
+        self.symbols.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// The table of the class or function `scope`, found in this (global) table
+    ///
+    /// `None` for the global scope, which is this table itself.
+    pub fn scope_table(&self, scope: &ScopeKind) -> Option<Ref<'_, SymbolTable>> {
+        match scope {
+            ScopeKind::Global => None,
+            ScopeKind::Class(name) | ScopeKind::Function(name) => {
+                Ref::filter_map(self.get(name)?.borrow(), |data| data.table.as_ref()).ok()
+            }
+        }
+    }
+
+    /// Find `name` in this scope, or in the scopes it's nested in
+    ///
+    /// Class scopes look through the classes they inherit from before their parent, depth-first
+    /// in `isa` order. Parent scopes are found in `global`.
+    pub fn lookup(&self, name: &str, global: &SymbolTable) -> Option<Rc<RefCell<SymbolData>>> {
+        if let Some(data) = self.get(name) {
+            return Some(data.clone());
+        }
+
+        let mut to_search = self.bases.iter().rev().cloned().collect::<Vec<_>>();
+        let mut searched: Vec<String> = vec![];
+
+        while let Some(class_name) = to_search.pop() {
+            // Inheritance cycles are reported elsewhere, just don't loop forever
+            if searched.contains(&class_name) {
+                continue;
+            }
+
+            if let Some(class_table) = global.scope_table(&ScopeKind::Class(class_name.clone())) {
+                if let Some(data) = class_table.get(name) {
+                    return Some(data.clone());
+                }
+
+                to_search.extend(class_table.bases.iter().rev().cloned());
+            }
+
+            searched.push(class_name);
+        }
+
+        match self.parent.as_ref()? {
+            ScopeKind::Global => global.get(name).cloned(),
+            parent => global.scope_table(parent)?.lookup(name, global),
+        }
+    }
+}
+
+impl Index<&str> for SymbolTable {
+    type Output = Rc<RefCell<SymbolData>>;
+
+    fn index(&self, name: &str) -> &Self::Output {
+        &self.symbols[name]
+    }
+}
+
+/// How a nested table is introduced, like `A::f(Integer) => Float` or `B isa A`
+fn fmt_scope_heading(key: &str, table: &SymbolTable) -> String {
+    match (&table.scope, &table.return_type) {
+        (ScopeKind::Function(_), Some(return_type)) => format!("{key} => {return_type}"),
+        (ScopeKind::Class(_), _) if !table.bases.is_empty() => {
+            format!("{key} isa {}", table.bases.join(", "))
+        }
+        _ => key.to_string(),
+    }
+}
+
+pub fn fmt_symbol_table(table: &SymbolTable) -> Result<String, std::fmt::Error> {
+    let longest_key = table
+        .keys()
+        .map(|s| s.len())
+        .max()
+        .unwrap_or(0)
+        .max(" Symbol ".len());
+
+    let longest_value = table
+        .values()
+        .map(|s| s.borrow().to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(" Data ".len());
+
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        "| {:=^longest_key$} | {:=^longest_value$} |",
+        " Symbol ", " Data "
+    )?;
+
+    let mut other_tables: Vec<(String, SymbolTable)> = Vec::new();
+
+    let mut to_print = table.iter().collect::<Vec<_>>();
+
+    to_print.sort_by(
+        |(k1, v1), (k2, v2)| match v2.borrow().offset.cmp(&v1.borrow().offset) {
+            std::cmp::Ordering::Equal => k1.cmp(k2),
+            ord => ord,
+        },
+    );
+
+    for (key, value) in to_print {
+        writeln!(
+            output,
+            "| {:<longest_key$} | {:<longest_value$} |",
+            key,
+            value.borrow().to_string(),
+        )?;
+
+        if let Some(other) = &value.borrow().table {
+            other_tables.push((key.clone(), other.clone()));
+        }
+    }
+
+    for (key, other) in other_tables {
+        let other_out = fmt_symbol_table(&other)?;
+        write!(
+            output,
+            "\n{}:\n{other_out}",
+            fmt_scope_heading(&key, &other)
+        )?;
+    }
+
+    Ok(output)
+}
+
+/// Every scope of the program as JSON, starting from the global table
+///
+/// Unlike `fmt_symbol_table` the output doesn't depend on hash ordering: scopes come in the order
+/// they're declared in (global scope first), and so do the entries of each scope. Symbols the
+/// compiler adds itself (like `self`) come last, by name.
+///
+/// ```json
+/// {
+///   "scopes": [
+///     {
+///       "name": "",
+///       "kind": "global",
+///       "parent": null,
+///       "entries": [
+///         {"name": "main()", "kind": "function", "type": "Function", "size": 4, "offset": 0, "visibility": null, "label": null, "location": {"line": 1, "column": 10}}
+///       ]
+///     },
+///     ...
+///   ]
+/// }
+/// ```
+///
+/// Class scopes also have `bases`, and function scopes `return_type` and `params`.
+pub fn symbol_table_json(global: &SymbolTable) -> String {
+    let mut scopes: Vec<String> = vec![];
+    push_json_scopes(global, &mut scopes);
+
+    format!("{{\n  \"scopes\": [\n{}\n  ]\n}}\n", scopes.join(",\n"))
+}
+
+fn push_json_scopes(table: &SymbolTable, scopes: &mut Vec<String>) {
+    let mut entries = table.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(name, data)| {
+        let location = data.borrow().location.clone();
+        (location.is_none(), location, name.to_string())
+    });
+
+    let mut scope = vec![
+        format!("      \"name\": {}", json_string(table.scope.name())),
+        format!("      \"kind\": \"{}\"", table.scope.kind_name()),
+        format!(
+            "      \"parent\": {}",
+            table
+                .parent
+                .as_ref()
+                .map_or(String::from("null"), |p| json_string(p.name()))
+        ),
+    ];
+
+    match &table.scope {
+        ScopeKind::Class(_) => scope.push(format!(
+            "      \"bases\": [{}]",
+            table
+                .bases
+                .iter()
+                .map(|b| json_string(b))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        ScopeKind::Function(_) => {
+            scope.push(format!(
+                "      \"return_type\": {}",
+                table
+                    .return_type
+                    .as_ref()
+                    .map_or(String::from("null"), |t| json_string(&t.to_string()))
+            ));
+            scope.push(format!(
+                "      \"params\": [{}]",
+                table
+                    .params
+                    .iter()
+                    .map(|p| json_string(&p.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        ScopeKind::Global => (),
+    }
+
+    let json_entries = entries
+        .iter()
+        .map(|(name, data)| format!("        {}", json_entry(name, &data.borrow())))
+        .collect::<Vec<_>>();
+    scope.push(if json_entries.is_empty() {
+        String::from("      \"entries\": []")
+    } else {
+        format!(
+            "      \"entries\": [\n{}\n      ]",
+            json_entries.join(",\n")
+        )
+    });
+
+    scopes.push(format!("    {{\n{}\n    }}", scope.join(",\n")));
+
+    // Objects link to their class' table too, only declarations are scopes
+    for (_, data) in entries {
+        let data = data.borrow();
+
+        if let (SymbolKind::Class | SymbolKind::Function, Some(nested)) = (data.kind, &data.table) {
+            push_json_scopes(nested, scopes);
+        }
+    }
+}
+
+fn json_entry(name: &str, data: &SymbolData) -> String {
+    let visibility = match data.visibility {
+        Some(Visibility::Public) => "\"public\"",
+        Some(Visibility::Private) => "\"private\"",
+        None => "null",
+    };
+    let location = data.location.as_ref().map_or(String::from("null"), |l| {
+        format!("{{\"line\": {}, \"column\": {}}}", l.line, l.column)
+    });
+
+    format!(
+        "{{\"name\": {}, \"kind\": \"{}\", \"type\": {}, \"size\": {}, \"offset\": {}, \"visibility\": {}, \"label\": {}, \"location\": {}}}",
+        json_string(name),
+        data.kind,
+        json_string(&data.var_type.to_string()),
+        data.size,
+        data.offset,
+        visibility,
+        data.label.as_ref().map_or(String::from("null"), |l| json_string(l)),
+        location,
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
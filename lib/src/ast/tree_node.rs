@@ -1,4 +1,7 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
 #[derive(Debug, Clone)]
 pub enum TreeNode {
@@ -76,3 +79,46 @@ impl Display for TreeNode {
         }
     }
 }
+
+impl FromStr for TreeNode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ArgumentList" => Ok(TreeNode::ArgumentList()),
+            "ArithExpr" => Ok(TreeNode::ArithExpr()),
+            "Assignment" => Ok(TreeNode::Assignment()),
+            "Attribute" => Ok(TreeNode::Attribute()),
+            "Class" => Ok(TreeNode::Class()),
+            "ClassMembers" => Ok(TreeNode::ClassMembers()),
+            "ConstructorFunc" => Ok(TreeNode::ConstructorFunc()),
+            "ElseBlock" => Ok(TreeNode::ElseBlock()),
+            "Expr" => Ok(TreeNode::Expr()),
+            "Factor" => Ok(TreeNode::Factor()),
+            "Function" => Ok(TreeNode::Function()),
+            "FunctionBody" => Ok(TreeNode::FunctionBody()),
+            "FunctionCall" => Ok(TreeNode::FunctionCall()),
+            "FunctionHead" => Ok(TreeNode::FunctionHead()),
+            "If" => Ok(TreeNode::If()),
+            "IfBlock" => Ok(TreeNode::IfBlock()),
+            "IndexedVar" => Ok(TreeNode::IndexedVar()),
+            "IndiceList" => Ok(TreeNode::IndiceList()),
+            "InheritsList" => Ok(TreeNode::InheritsList()),
+            "LocalVarDecl" => Ok(TreeNode::LocalVarDecl()),
+            "MemberFunc" => Ok(TreeNode::MemberFunc()),
+            "NestedVar" => Ok(TreeNode::NestedVar()),
+            "Parameter" => Ok(TreeNode::Parameter()),
+            "ParameterList" => Ok(TreeNode::ParameterList()),
+            "Program" => Ok(TreeNode::Program()),
+            "Read" => Ok(TreeNode::Read()),
+            "RelExpr" => Ok(TreeNode::RelExpr()),
+            "Return" => Ok(TreeNode::Return()),
+            "Scope" => Ok(TreeNode::Scope()),
+            "Variable" => Ok(TreeNode::Variable()),
+            "While" => Ok(TreeNode::While()),
+            "WhileBlock" => Ok(TreeNode::WhileBlock()),
+            "Write" => Ok(TreeNode::Write()),
+            _ => Err(format!("Unknown tree node '{s}'")),
+        }
+    }
+}
@@ -1,16 +1,26 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::{
-    ast::nodes::{CodeNode, NodeValue, VarType},
-    compiler_error::CompilerError,
+    ast::{
+        nodes::{CodeNode, NodeValue, StructNode, VarType},
+        symbol_table::{ScopeKind, SymbolKind, SymbolTable},
+    },
+    compiler_error::{CompilerError, CompilerResult},
     lexical::tokens::token_type::Type,
     semantic::{
+        class_layout::ClassLayouts,
+        constants::const_int,
         visitor::{Visitor, VisitorResult, FLOAT_SIZE, INT_SIZE},
-        visitor_utils::{get_current_function, get_global_table, get_symbol_data},
+        visitor_utils::{
+            get_base_offset, get_class_member, get_current_class, get_current_function,
+            get_symbol_data,
+        },
     },
 };
 
-pub struct CodegenVisitor {
+pub struct CodegenVisitor<'a> {
+    global: &'a SymbolTable,
+    layouts: &'a ClassLayouts,
     alloc: String,
     code: String,
     label_count: AtomicUsize,
@@ -33,9 +43,11 @@ lw r3, -8(r12)
 lw r4, -12(r12)
 ";
 
-impl CodegenVisitor {
-    pub fn new() -> Self {
+impl<'a> CodegenVisitor<'a> {
+    pub fn new(global: &'a SymbolTable, layouts: &'a ClassLayouts) -> Self {
         Self {
+            global,
+            layouts,
             alloc: String::from("strbuf res 32\nregbuf res 16\n"),
             code: String::from(""),
             label_count: AtomicUsize::new(0),
@@ -43,9 +55,13 @@ impl CodegenVisitor {
         }
     }
 
+    /// Take a free register for `node`, failing if every one is in use (which also happens when the tree
+    /// failed to check and codegen is forced, since failed nodes never free theirs)
     #[track_caller]
-    fn get_register(&mut self) -> String {
-        self.registers.pop().unwrap()
+    fn get_register(&mut self, node: &StructNode) -> CompilerResult<String> {
+        self.registers.pop().ok_or_else(|| {
+            CompilerError::new_with_node(String::from("Ran out of registers!"), node).into()
+        })
         // let reg = self.registers.pop().unwrap();
         // eprintln!(
         //     " Taking register: {:<3} ({:>2} left) {}",
@@ -57,7 +73,7 @@ impl CodegenVisitor {
     }
 
     fn free_register(&mut self, reg: String) {
-        if reg == "r0" || reg == "r13" {
+        if reg == "r0" || reg == "r13" || reg == "r14" {
             return;
         }
         // eprintln!(
@@ -68,6 +84,202 @@ impl CodegenVisitor {
         self.registers.push(reg);
     }
 
+    /// Get the value `label` refers to into a register
+    ///
+    /// Labels of members and array elements (like `-8(r3)`) already hold their address in a
+    /// register, which is reused since the address isn't needed after loading
+    fn load_label(
+        &mut self,
+        code: &mut String,
+        node: &StructNode,
+        label: String,
+    ) -> CompilerResult<String> {
+        if is_reg(&label) {
+            return Ok(label);
+        }
+
+        let reg = match split_label(&label) {
+            Some((_, base)) if base != "r14" => base,
+            _ => self.get_register(node)?,
+        };
+
+        code.push_str(&format!("lw {reg}, {label}\n"));
+        Ok(reg)
+    }
+
+    /// Get the address `label` refers to into a register, for arrays passed by reference
+    ///
+    /// Like `load_label`, the register holding the base of the label is reused.
+    fn address_of(
+        &mut self,
+        code: &mut String,
+        node: &StructNode,
+        label: String,
+    ) -> CompilerResult<String> {
+        let (offset, base_reg) = split_label(&label).ok_or_else(|| {
+            CompilerError::new_with_node(
+                format!("Cannot pass '{label}' by reference, it isn't stored anywhere!"),
+                node,
+            )
+        })?;
+
+        let reg = match base_reg.as_str() {
+            "r14" => self.get_register(node)?,
+            _ => base_reg.clone(),
+        };
+
+        code.push_str(&format!("addi {reg}, {base_reg}, {offset}\n"));
+        Ok(reg)
+    }
+
+    /// Free whatever register `label` uses, either the value itself or the address it's stored at
+    fn free_label(&mut self, label: String) {
+        if is_reg(&label) {
+            self.free_register(label);
+        } else if let Some((_, base)) = split_label(&label) {
+            self.free_register(base);
+        }
+    }
+
+    /// Offset of `member` in objects of `class_name`, which may have inherited it
+    fn member_offset(&self, class_name: &str, member: &str) -> Option<isize> {
+        let (owner, _) = get_class_member(self.global, class_name, member)?;
+        let layout = self.layouts.get(class_name)?;
+        layout.member(&owner, member).map(|m| m.offset)
+    }
+
+    /// Size of a single element of `var_type`, ie. 4 for an `integer[4][2]`
+    fn get_element_size(&self, node: &CodeNode, var_type: &VarType) -> CompilerResult<usize> {
+        match var_type {
+            VarType::Integer(_) => Ok(INT_SIZE),
+            VarType::Float(_) => Ok(FLOAT_SIZE),
+            VarType::Class(class_name, _) => {
+                let class_data = self.global.get(class_name).ok_or_else(|| {
+                    CompilerError::new_with_node(
+                        format!("Class '{class_name}' not found!"),
+                        &node.borrow(),
+                    )
+                })?;
+                let size = class_data.borrow().size;
+                Ok(size)
+            }
+            _ => Err(CompilerError::new_with_node(
+                format!("Cannot get the size of '{var_type}'!"),
+                &node.borrow(),
+            )
+            .into()),
+        }
+    }
+
+    /// Size of the frame of the function `node` is in: its parameters and locals, the return
+    /// address and room for what it returns
+    fn frame_size(&self, node: &CodeNode) -> CompilerResult<usize> {
+        let function = get_current_function(node).ok_or_else(|| {
+            CompilerError::new(
+                format!("Expected parent function at {}!", node),
+                node.borrow().token.clone(),
+            )
+        })?;
+
+        let function_ref = function.borrow();
+        let table_ref = function_ref.symbol_table.borrow();
+        let table = table_ref.as_ref().ok_or_else(|| {
+            CompilerError::new(
+                "Function has no symbol table!".to_string(),
+                node.borrow().token.clone(),
+            )
+        })?;
+
+        let symbols_size = table.values().fold(0, |acc, v| acc + v.borrow().size);
+        let return_size = match &table.return_type {
+            Some(VarType::Void) | None => 0,
+            Some(return_type) => self.get_element_size(node, return_type)?,
+        };
+
+        Ok(symbols_size + return_size + INT_SIZE)
+    }
+
+    /// Offset the label of an array (`base`) by `indices`, returning the label of the element
+    ///
+    /// Arrays are laid out like everything else, going down from their label, so `arr[i][j]` of an
+    /// `integer[4][2]` is at `arr - (i * 2 + j) * 4`
+    fn index_label(
+        &mut self,
+        code: &mut String,
+        base: String,
+        var_type: &VarType,
+        indices: &CodeNode,
+    ) -> CompilerResult<String> {
+        if indices.children().count() == 0 {
+            return Ok(base);
+        }
+
+        let (offset, base_reg) = split_label(&base).ok_or_else(|| {
+            CompilerError::new_with_node(
+                format!("Expected a memory label to index, found '{base}'!"),
+                &indices.borrow(),
+            )
+        })?;
+
+        let element_size = self.get_element_size(indices, var_type)?;
+        let dimensions = var_type.dimensions();
+        let mut offset = offset;
+        let mut computed = vec![];
+
+        for (i, index) in indices.children().enumerate() {
+            let index_label = index.borrow().label.borrow().clone().ok_or_else(|| {
+                CompilerError::new(
+                    format!("Expected a label at {}", index),
+                    index.borrow().token.clone(),
+                )
+            })?;
+
+            // Each index skips over all the elements in the dimensions after it
+            let stride = element_size
+                * dimensions
+                    .get(i + 1..)
+                    .map_or(1, |rest| rest.iter().product::<usize>());
+
+            // Constant indices are part of the offset, their value is never needed
+            match const_int(&index) {
+                Some(value) => {
+                    offset -= value * stride as isize;
+                    self.free_label(index_label);
+                }
+                None => computed.push((index, index_label, stride)),
+            }
+        }
+
+        if computed.is_empty() {
+            return Ok(format!("{offset}({base_reg})"));
+        }
+
+        // The index expressions might call functions, so they go before we hold onto any address
+        for (index, _, _) in &computed {
+            if let Some(index_code) = index.borrow().code.borrow().clone() {
+                code.push_str(&index_code);
+            }
+        }
+
+        let addr_reg = if base_reg == "r14" {
+            self.get_register(&indices.borrow())?
+        } else {
+            base_reg.clone()
+        };
+
+        code.push_str("% index array\n");
+        code.push_str(&format!("addi {addr_reg}, {base_reg}, {offset}\n"));
+
+        for (_, index_label, stride) in computed {
+            let index_reg = self.load_label(code, &indices.borrow(), index_label)?;
+            code.push_str(&format!("muli {index_reg}, {index_reg}, {stride}\n"));
+            code.push_str(&format!("sub {addr_reg}, {addr_reg}, {index_reg}\n"));
+            self.free_register(index_reg);
+        }
+
+        Ok(format!("0({addr_reg})"))
+    }
+
     pub fn new_temp_label(&self) -> String {
         format!("t{}", self.label_count.fetch_add(1, Ordering::SeqCst))
     }
@@ -92,19 +304,13 @@ hlt
     }
 }
 
-impl Default for CodegenVisitor {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl Visitor for CodegenVisitor {
+impl Visitor for CodegenVisitor<'_> {
     fn visit_local_var_decl(
         &mut self,
         node: &CodeNode,
         id: Type,
         _type_: Type,
-        _indice_or_args: CodeNode,
+        indice_or_args: CodeNode,
     ) -> VisitorResult {
         let id = match id {
             Type::Id(ref id) => id,
@@ -128,7 +334,19 @@ impl Visitor for CodegenVisitor {
         let label = format!("{offset}(r14)");
 
         symbol_data.borrow_mut().label = Some(label.clone());
-        node.borrow().label.borrow_mut().replace(label);
+        node.borrow().label.borrow_mut().replace(label.clone());
+
+        // Objects declared with arguments are initialised by their constructor, with `self`
+        // pointing to where they are in the frame
+        let target = node.borrow().target.borrow().clone();
+        if let Some(target) = target {
+            let args = indice_or_args.children().collect::<Vec<_>>();
+            let mut code = self.call_code(node, &target, &args, Some(label), None)?;
+            code.push_str(&format!("% End constructor call {target}\n\n"));
+
+            node.borrow().code.borrow_mut().replace(code);
+        }
+
         Ok(())
     }
 
@@ -136,9 +354,10 @@ impl Visitor for CodegenVisitor {
         &mut self,
         node: &CodeNode,
         id: Type,
-        _indices: Option<CodeNode>,
+        indices: Option<CodeNode>,
     ) -> VisitorResult {
         let id_str = match id {
+            Type::SelfT => String::from("self"),
             Type::Id(id) => id,
             _ => {
                 return Err(CompilerError::new(
@@ -157,7 +376,121 @@ impl Visitor for CodegenVisitor {
         })?;
 
         let offset = symbol_data.borrow().offset;
-        let label = format!("{offset}(r14)");
+        let var_type = symbol_data.borrow().var_type.clone();
+        let kind = symbol_data.borrow().kind;
+        let mut code = String::new();
+
+        let label = if id_str == "self" {
+            // self only holds the address of the object
+            let reg = self.get_register(&node.borrow())?;
+            code.push_str(&format!("% load self\nlw {reg}, {offset}(r14)\n"));
+            format!("0({reg})")
+        } else if kind == SymbolKind::Parameter && !var_type.dimensions().is_empty() {
+            // Array parameters only hold the address of the array they were passed
+            let reg = self.get_register(&node.borrow())?;
+            code.push_str(&format!("% load array address\nlw {reg}, {offset}(r14)\n"));
+            format!("0({reg})")
+        } else if kind == SymbolKind::Attribute {
+            // Attributes used by name are members of self, at their offset in its class
+            let self_offset = get_symbol_data(node, "self")
+                .map(|data| data.borrow().offset)
+                .ok_or_else(|| {
+                    CompilerError::new_with_node(
+                        format!("Attribute '{id_str}' used outside of a member function!"),
+                        &node.borrow(),
+                    )
+                })?;
+            let member_offset = get_current_class(node)
+                .and_then(|class_name| self.member_offset(&class_name, &id_str))
+                .ok_or_else(|| {
+                    CompilerError::new_with_node(
+                        format!("Found no offset for attribute '{id_str}'!"),
+                        &node.borrow(),
+                    )
+                })?;
+
+            let reg = self.get_register(&node.borrow())?;
+            code.push_str(&format!("% load self\nlw {reg}, {self_offset}(r14)\n"));
+            format!("{member_offset}({reg})")
+        } else {
+            format!("{offset}(r14)")
+        };
+
+        let label = match indices {
+            Some(indices) => self.index_label(&mut code, label, &var_type, &indices)?,
+            None => label,
+        };
+
+        if !code.is_empty() {
+            node.borrow().code.borrow_mut().replace(code);
+        }
+
+        node.borrow().label.borrow_mut().replace(label);
+        Ok(())
+    }
+
+    fn visit_nested_var(
+        &mut self,
+        node: &CodeNode,
+        object: CodeNode,
+        id: Type,
+        indices: CodeNode,
+    ) -> VisitorResult {
+        let member = match id {
+            Type::Id(id) => id,
+            _ => {
+                return Err(CompilerError::new(
+                    format!("Expected identifier at '{}'!", node.borrow().value),
+                    node.borrow().token.clone(),
+                )
+                .into())
+            }
+        };
+
+        let object_ref = object.borrow();
+        let object_label = object_ref.label.borrow().clone().unwrap_or_default();
+
+        // Objects returned from functions only exist in r13
+        let (object_offset, base_reg) = split_label(&object_label).ok_or_else(|| {
+            CompilerError::new_with_node(
+                format!("Cannot access member '{member}', the object isn't stored anywhere!"),
+                &node.borrow(),
+            )
+        })?;
+
+        let class_name = match object_ref.var_type.borrow().clone() {
+            Some(VarType::Class(class_name, _)) => class_name,
+            _ => {
+                return Err(CompilerError::new_with_node(
+                    format!("Expected an object to access member '{member}' of!"),
+                    &node.borrow(),
+                )
+                .into())
+            }
+        };
+
+        let member_error = || {
+            CompilerError::new_with_node(
+                format!("Class '{class_name}' has no member '{member}'!"),
+                &node.borrow(),
+            )
+        };
+
+        let (_, member_data) =
+            get_class_member(self.global, &class_name, &member).ok_or_else(member_error)?;
+        let member_offset = self
+            .member_offset(&class_name, &member)
+            .ok_or_else(member_error)?;
+        let member_type = member_data.borrow().var_type.clone();
+
+        // Members are at a fixed offset from the start of the object
+        let mut code = object_ref.code.borrow().clone().unwrap_or_default();
+        let label = format!("{}({base_reg})", object_offset + member_offset);
+        let label = self.index_label(&mut code, label, &member_type, &indices)?;
+
+        if !code.is_empty() {
+            node.borrow().code.borrow_mut().replace(code);
+        }
 
         node.borrow().label.borrow_mut().replace(label);
         Ok(())
@@ -167,7 +500,7 @@ impl Visitor for CodegenVisitor {
         let (key, size, lit, bytes) = get_literal_label(&factor);
 
         let mut code = String::new();
-        let reg = self.get_register();
+        let reg = self.get_register(&node.borrow())?;
 
         if size == INT_SIZE {
             code.push_str(&format!("% assign literal {lit}\n"));
@@ -190,6 +523,51 @@ impl Visitor for CodegenVisitor {
         Ok(())
     }
 
+    fn visit_unary_factor(&mut self, node: &CodeNode, op: Type, factor: CodeNode) -> VisitorResult {
+        let factor_ref = factor.borrow();
+        let factor_label = factor_ref.label.borrow().clone().ok_or_else(|| {
+            CompilerError::new(
+                format!("Expected a label at {}", factor),
+                factor_ref.token.clone(),
+            )
+        })?;
+
+        let mut code = factor_ref.code.borrow().clone().unwrap_or_default();
+
+        // `+x` is just `x`
+        if op == Type::Plus {
+            node.borrow().label.borrow_mut().replace(factor_label);
+            node.borrow().code.borrow_mut().replace(code);
+            return Ok(());
+        }
+
+        let reg = self.get_register(&node.borrow())?;
+        let f_reg = self.load_label(&mut code, &node.borrow(), factor_label)?;
+
+        code.push_str(&format!("% unary {op}\n"));
+        match op {
+            Type::Minus => code.push_str(&format!("sub {reg}, r0, {f_reg}\n")),
+            // Logical not, MOON's `not` is bitwise
+            _ => code.push_str(&format!("ceq {reg}, {f_reg}, r0\n")),
+        }
+
+        self.free_register(f_reg);
+
+        node.borrow().label.borrow_mut().replace(reg);
+        node.borrow().code.borrow_mut().replace(code);
+        Ok(())
+    }
+
+    fn visit_paren_factor(&mut self, node: &CodeNode, expr: CodeNode) -> VisitorResult {
+        let node_ref = node.borrow();
+        let expr_ref = expr.borrow();
+
+        *node_ref.label.borrow_mut() = expr_ref.label.borrow().clone();
+        *node_ref.code.borrow_mut() = expr_ref.code.borrow().clone();
+
+        Ok(())
+    }
+
     fn visit_rel_expr(
         &mut self,
         node: &CodeNode,
@@ -231,7 +609,7 @@ impl Visitor for CodegenVisitor {
             }
         };
 
-        let reg = self.get_register();
+        let reg = self.get_register(&node.borrow())?;
         let mut code = String::new();
 
         code.push_str("% rel expression\n");
@@ -244,21 +622,8 @@ impl Visitor for CodegenVisitor {
             code.push_str(&right_code);
         }
 
-        let l_reg = if is_reg(&left_label) {
-            left_label
-        } else {
-            let reg = self.get_register();
-            code.push_str(&format!("lw {reg}, {left_label}\n"));
-            reg
-        };
-
-        let r_reg = if is_reg(&right_label) {
-            right_label
-        } else {
-            let reg = self.get_register();
-            code.push_str(&format!("lw {reg}, {right_label}\n"));
-            reg
-        };
+        let l_reg = self.load_label(&mut code, &node.borrow(), left_label)?;
+        let r_reg = self.load_label(&mut code, &node.borrow(), right_label)?;
 
         code.push_str(&format!(
             "{operand} {reg}, {l_reg}, {r_reg}\n% end rel expression\n"
@@ -303,6 +668,8 @@ impl Visitor for CodegenVisitor {
                 Type::Minus => "sub",
                 Type::Mult => "mul",
                 Type::Div => "div",
+                Type::And => "and",
+                Type::Or => "or",
                 _ => {
                     return Err(CompilerError::new(
                         format!("Expected operator at {}!", op),
@@ -320,7 +687,7 @@ impl Visitor for CodegenVisitor {
             }
         };
 
-        let reg = self.get_register();
+        let reg = self.get_register(&node.borrow())?;
         let mut code = String::new();
 
         code.push_str("% arith expression\n");
@@ -333,25 +700,25 @@ impl Visitor for CodegenVisitor {
             code.push_str(&right_code);
         }
 
-        let l_reg = if is_reg(&left_label) {
-            left_label
-        } else {
-            let reg = self.get_register();
-            code.push_str(&format!("lw {reg}, {left_label}\n"));
-            reg
-        };
-
-        let r_reg = if is_reg(&right_label) {
-            right_label
-        } else {
-            let reg = self.get_register();
-            code.push_str(&format!("lw {reg}, {right_label}\n"));
-            reg
-        };
-
-        code.push_str(&format!(
-            "{operand} {reg}, {l_reg}, {r_reg}\n% end arith expression\n"
-        ));
+        let l_reg = self.load_label(&mut code, &node.borrow(), left_label)?;
+        let r_reg = self.load_label(&mut code, &node.borrow(), right_label)?;
+
+        // `and`/`or` are logical, but MOON's are bitwise, so turn the operands into 0 or 1 first
+        match operand {
+            "and" => {
+                let t_reg = self.get_register(&node.borrow())?;
+                code.push_str(&format!("cne {reg}, {l_reg}, r0\n"));
+                code.push_str(&format!("cne {t_reg}, {r_reg}, r0\n"));
+                code.push_str(&format!("mul {reg}, {reg}, {t_reg}\n"));
+                self.free_register(t_reg);
+            }
+            "or" => {
+                code.push_str(&format!("or {reg}, {l_reg}, {r_reg}\n"));
+                code.push_str(&format!("cne {reg}, {reg}, r0\n"));
+            }
+            _ => code.push_str(&format!("{operand} {reg}, {l_reg}, {r_reg}\n")),
+        }
+        code.push_str("% end arith expression\n");
 
         self.free_register(l_reg);
         self.free_register(r_reg);
@@ -397,23 +764,24 @@ impl Visitor for CodegenVisitor {
         if let Some(expr_label) = expr_label {
             let expr_code = expr.borrow().code.borrow().clone();
 
+            let variable_code = variable.borrow().code.borrow().clone();
+
             let mut code = String::new();
 
             code.push_str("% assignment\n");
+            if let Some(variable_code) = variable_code {
+                code.push_str(&variable_code);
+            }
+
             if let Some(expr_code) = expr_code {
                 code.push_str(&expr_code);
             }
 
-            let expr_reg = if is_reg(&expr_label) {
-                expr_label
-            } else {
-                let reg = self.get_register();
-                code.push_str(&format!("lw {reg}, {expr_label}\n"));
-                reg
-            };
+            let expr_reg = self.load_label(&mut code, &node.borrow(), expr_label)?;
 
             code.push_str(&format!("sw {variable_label}, {expr_reg}\n\n"));
             self.free_register(expr_reg);
+            self.free_label(variable_label);
             node.borrow().code.borrow_mut().replace(code);
 
             Ok(())
@@ -536,40 +904,24 @@ impl Visitor for CodegenVisitor {
         // store r13 in variable
         // continue
 
-        let cur_func = get_current_function(node).ok_or_else(|| {
+        let func_size = self.frame_size(node)?;
+
+        let var_label = variable.borrow().label.borrow().clone().ok_or_else(|| {
             CompilerError::new(
-                format!("Expected parent function at {}!", node),
-                node.borrow().token.clone(),
+                format!("Expected a label at {}", variable),
+                variable.borrow().token.clone(),
             )
         })?;
 
-        let func_size = cur_func
-            .borrow()
-            .symbol_table
-            .borrow()
-            .clone()
-            .unwrap()
-            .values()
-            .fold(0, |acc, x| acc + x.borrow().size);
-
-        let var_label = variable
-            .borrow()
-            .label
-            .borrow()
-            .clone()
-            .expect("Expected label!");
-
         let var_code = variable.borrow().code.borrow().clone().unwrap_or_default();
 
         let mut code = String::new();
         code.push_str("% Read()\n");
         code.push_str(SAVE_REGISTERS);
-        // run any var init code
-        code.push_str(&var_code);
         // inc stack pointer
         code.push_str(&format!("addi r14, r14, -{func_size}\n"));
         // store buffer pointer at -8(r14)
-        let buf_reg = self.get_register();
+        let buf_reg = self.get_register(&node.borrow())?;
         code.push_str(&format!("addi {buf_reg}, r0, strbuf\n"));
         code.push_str(&format!("sw -8(r14), {buf_reg}\n"));
         self.free_register(buf_reg);
@@ -579,8 +931,11 @@ impl Visitor for CodegenVisitor {
         code.push_str("jl r15, strint\n");
         // decr stack pointer
         code.push_str(&format!("addi r14, r14, {func_size}\n"));
+        // run any var init code, after the calls since they don't keep our registers
+        code.push_str(&var_code);
         // store r13 in variable
         code.push_str(&format!("sw {var_label}, r13\n"));
+        self.free_label(var_label);
         code.push_str(RESTORE_REGISTERS);
 
         node.borrow().code.borrow_mut().replace(code);
@@ -600,30 +955,15 @@ impl Visitor for CodegenVisitor {
         // decr stack pointer r14
         // continue
 
-        let cur_func = get_current_function(node).ok_or_else(|| {
+        let func_size = self.frame_size(node)? + 4;
+
+        let expr_label = expr.borrow().label.borrow().clone().ok_or_else(|| {
             CompilerError::new(
-                format!("Expected parent function at {}!", node),
-                node.borrow().token.clone(),
+                format!("Expected a label at {}", expr),
+                expr.borrow().token.clone(),
             )
         })?;
 
-        let func_size = cur_func
-            .borrow()
-            .symbol_table
-            .borrow()
-            .clone()
-            .unwrap()
-            .values()
-            .fold(0, |acc, x| acc + x.borrow().size)
-            + 4;
-
-        let expr_label = expr
-            .borrow()
-            .label
-            .borrow()
-            .clone()
-            .expect("Expected label!");
-
         let expr_code = expr.borrow().code.borrow().clone().unwrap_or_default();
 
         let mut code = String::new();
@@ -631,7 +971,7 @@ impl Visitor for CodegenVisitor {
         code.push_str("% Write()\n");
         code.push_str(SAVE_REGISTERS);
         code.push_str(&expr_code);
-        let expr_reg = self.get_register();
+        let expr_reg = self.get_register(&node.borrow())?;
 
         // store expr int
         code.push_str("% store expr int\n");
@@ -640,6 +980,7 @@ impl Visitor for CodegenVisitor {
             self.free_register(expr_label);
         } else {
             code.push_str(&format!("lw {expr_reg}, {expr_label}\n"));
+            self.free_label(expr_label);
         }
         code.push_str("% write call\n");
         // inc stack pointer
@@ -647,7 +988,7 @@ impl Visitor for CodegenVisitor {
         code.push_str(&format!("sw -8(r14), {expr_reg}\n"));
         self.free_register(expr_reg);
         // store buffer pointer
-        let buf_reg = self.get_register();
+        let buf_reg = self.get_register(&node.borrow())?;
         code.push_str(&format!("addi {buf_reg}, r0, strbuf\n"));
         code.push_str(&format!("sw -12(r14), {buf_reg}\n"));
         self.free_register(buf_reg);
@@ -687,45 +1028,12 @@ impl Visitor for CodegenVisitor {
             )
         })?;
 
-        let expr_type = expr_ref.var_type.borrow().clone().ok_or_else(|| {
-            CompilerError::new(
-                "Failed to get type of expression".to_string(),
-                expr_ref.token.clone(),
-            )
-        })?;
-
-        let func_table = get_current_function(node)
-            .unwrap()
-            .borrow()
-            .symbol_table
-            .clone();
-
-        let func_table = func_table.borrow().clone().unwrap();
-
-        let (_, return_type) =
-            func_table
-                .iter()
-                .find(|(k, _)| k == &"_return")
-                .ok_or(CompilerError::new(
-                    "Function return type not found in head table".to_string(),
-                    node_ref.token.clone(),
-                ))?;
-
-        let return_type = return_type.borrow().clone().var_type;
-
-        if expr_type != return_type {
-            return Err(CompilerError::new(
-                format!("Return type mismatch (expected {return_type}, got {expr_type})!",),
-                node_ref.token.clone(),
-            )
-            .into());
-        }
-
         if is_reg(&label) {
             code.push_str(&format!("add r13, r0, {label}\n"));
             self.free_register(label);
         } else {
             code.push_str(&format!("lw r13, {label}\n"));
+            self.free_label(label);
         }
 
         node_ref.code.borrow_mut().replace(code);
@@ -754,17 +1062,23 @@ impl Visitor for CodegenVisitor {
         let head_ref = head.borrow();
         let head_table = head_ref.symbol_table.borrow();
 
-        let (func_name, _) = head_table
-            .as_ref()
-            .unwrap()
-            .iter()
-            .find(|(_, value)| value.borrow().var_type == VarType::Function)
-            .ok_or(CompilerError::new(
-                "Function not found in head table".to_string(),
-                node_ref.token.clone(),
-            ))?;
+        let func_name = match head_table.as_ref().map(|t| &t.scope) {
+            Some(ScopeKind::Function(func_name)) => func_name,
+            _ => {
+                return Err(CompilerError::new(
+                    "Function not found in head table".to_string(),
+                    node_ref.token.clone(),
+                )
+                .into())
+            }
+        };
 
-        let func_label = head_ref.label.borrow().clone().unwrap();
+        let func_label = head_ref.label.borrow().clone().ok_or_else(|| {
+            CompilerError::new(
+                format!("Expected a label at {}", head),
+                head_ref.token.clone(),
+            )
+        })?;
 
         let mut code = String::new();
 
@@ -791,10 +1105,12 @@ impl Visitor for CodegenVisitor {
     fn visit_function_call(
         &mut self,
         node: &CodeNode,
+        object: Option<CodeNode>,
         id: Type,
         param_list: CodeNode,
     ) -> VisitorResult {
         let node_ref = node.borrow();
+
         let func_name = match id {
             Type::Id(id) => id,
             _ => {
@@ -806,53 +1122,121 @@ impl Visitor for CodegenVisitor {
             }
         };
 
-        let param_types = param_list
-            .children()
-            .map(|c| {
-                let c_ref = c.borrow();
-                let c_type = c_ref.var_type.borrow().clone();
-                c_type.map_or_else(|| String::from("?"), |v| v.to_string())
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        let func_signature = format!("{}({})", func_name, param_types);
-        let global_table = get_global_table(node)?;
-
-        let func_data = global_table.get(&func_signature).ok_or_else(|| {
-            node_ref
-                .code
-                .borrow_mut()
-                .replace("addi r13, r0 , 0".to_string());
-            node_ref.label.borrow_mut().replace("r13".to_string());
+        // The symbol table pass picked which overload gets called
+        let target = node_ref.target.borrow().clone().ok_or_else(|| {
             CompilerError::new(
-                format!(
-                    "Failed to generate function call, '{}' not found!",
-                    func_signature
-                ),
+                format!("Failed to generate function call, '{func_name}' wasn't resolved!"),
                 node_ref.token.clone(),
             )
         })?;
-        let func_label = func_data.borrow().label.clone().unwrap();
-        let func_table = func_data.borrow().table.clone().unwrap();
 
-        let us_func = get_current_function(node).ok_or_else(|| {
+        let mut code = String::new();
+
+        // Member functions are called on an object, or on `self` if there's none
+        let mut self_code = None;
+        let mut object_class = get_current_class(node);
+        let object_label = match object {
+            Some(object) => {
+                let object_ref = object.borrow();
+                code.push_str(&object_ref.code.borrow().clone().unwrap_or_default());
+                object_class = match object_ref.var_type.borrow().clone() {
+                    Some(VarType::Class(class_name, _)) => Some(class_name),
+                    _ => None,
+                };
+                let object_label = object_ref.label.borrow().clone();
+                object_label
+            }
+            None if target.contains("::") => {
+                let self_data = get_symbol_data(node, "self").ok_or_else(|| {
+                    CompilerError::new_with_node(
+                        format!("Cannot call '{target}' without an object!"),
+                        &node_ref,
+                    )
+                })?;
+                let offset = self_data.borrow().offset;
+
+                // Only loaded once the arguments are computed, their code could use the register
+                let reg = self.get_register(&node_ref)?;
+                self_code = Some(format!("% load self\nlw {reg}, {offset}(r14)\n"));
+                Some(format!("0({reg})"))
+            }
+            None => None,
+        };
+
+        // A base's member function gets `self` pointing to that base's part of the object
+        let object_label = match (object_label, object_class, target.split_once("::")) {
+            (Some(object_label), Some(object_class), Some((owner, _))) => {
+                let base_offset =
+                    get_base_offset(self.global, &object_class, owner).ok_or_else(|| {
+                        CompilerError::new_with_node(
+                            format!(
+                                "Cannot call '{target}' on an object of class '{object_class}'!"
+                            ),
+                            &node_ref,
+                        )
+                    })?;
+
+                match split_label(&object_label) {
+                    Some((offset, base_reg)) => {
+                        Some(format!("{}({base_reg})", offset + base_offset))
+                    }
+                    None => Some(object_label),
+                }
+            }
+            (object_label, _, _) => object_label,
+        };
+
+        let args = param_list.children().collect::<Vec<_>>();
+        code.push_str(&self.call_code(node, &target, &args, object_label, self_code)?);
+
+        // return is always stored in 13
+        let ret_reg = self.get_register(&node_ref)?;
+        code.push_str("% Store return value\n");
+        code.push_str(&format!("add {ret_reg}, r0, r13\n"));
+
+        code.push_str(&format!("% End function call {target}\n\n"));
+
+        node_ref.code.borrow_mut().replace(code);
+        node_ref.label.borrow_mut().replace(ret_reg);
+        Ok(())
+    }
+}
+
+impl CodegenVisitor<'_> {
+    /// Call the function `target` (a global table key) with `args`, from inside `node`'s function
+    ///
+    /// Member functions get the address of the object at `object_label` as `self`, `object_code`
+    /// loads its base register after the arguments are computed. The return value is left in r13.
+    fn call_code(
+        &mut self,
+        node: &CodeNode,
+        target: &str,
+        args: &[CodeNode],
+        object_label: Option<String>,
+        object_code: Option<String>,
+    ) -> CompilerResult<String> {
+        let node_ref = node.borrow();
+        let func_data = self.global.get(target).ok_or_else(|| {
             CompilerError::new(
-                "Function call not inside a function!".to_string(),
+                format!("Failed to generate function call, '{target}' not found!"),
                 node_ref.token.clone(),
             )
         })?;
-        let us_ref = us_func.borrow();
-        let us_size = us_ref
-            .symbol_table
-            .borrow()
-            .as_ref()
-            .unwrap()
-            .values()
-            .fold(0, |acc, v| {
-                let v_ref = v.borrow();
-                acc + v_ref.size
-            });
+        let (func_label, func_table) = match (
+            func_data.borrow().label.clone(),
+            func_data.borrow().table.clone(),
+        ) {
+            (Some(func_label), Some(func_table)) => (func_label, func_table),
+            _ => {
+                return Err(CompilerError::new(
+                    format!("Failed to generate function call, '{target}' has no label or table!"),
+                    node_ref.token.clone(),
+                )
+                .into())
+            }
+        };
+
+        let us_size = self.frame_size(node)?;
 
         // We know the function exists, and that the types match
         // so now we need to:
@@ -862,9 +1246,9 @@ impl Visitor for CodegenVisitor {
 
         // Setup the stack frame
         let mut code = String::new();
-        code.push_str(&format!("% Call function {func_signature}\n"));
+        code.push_str(&format!("% Call function {target}\n"));
         // Run the init code for the params, if any so `add(1 + 3, a + b)` works
-        for child in param_list.children() {
+        for child in args {
             if let Some(child_code) = child.borrow().code.borrow().clone() {
                 code.push_str(&child_code);
             };
@@ -875,11 +1259,24 @@ impl Visitor for CodegenVisitor {
         code.push_str("% Push parameters\n");
 
         let mut push_code = String::new();
+        // Only free these once every parameter is loaded, they're pushed after
+        let mut pushed_regs: Vec<String> = vec![];
 
-        for child in param_list.children() {
+        for child in args {
             let child_ref = child.borrow();
-            let child_label = child_ref.label.borrow().clone().unwrap();
-            let child_type = child_ref.var_type.borrow().clone().unwrap();
+            let (child_label, child_type) = match (
+                child_ref.label.borrow().clone(),
+                child_ref.var_type.borrow().clone(),
+            ) {
+                (Some(child_label), Some(child_type)) => (child_label, child_type),
+                _ => {
+                    return Err(CompilerError::new(
+                        format!("Expected a label and type at {}", child),
+                        child_ref.token.clone(),
+                    )
+                    .into())
+                }
+            };
 
             let (param_name, param_data) = func_table
                 .iter()
@@ -891,7 +1288,7 @@ impl Visitor for CodegenVisitor {
                     CompilerError::new(
                         format!(
                             "Parameter '{}' not found in function '{}'!",
-                            child_type, func_signature
+                            child_type, target
                         ),
                         node_ref.token.clone(),
                     )
@@ -900,20 +1297,40 @@ impl Visitor for CodegenVisitor {
             let param_size = param_data.borrow().size;
 
             push_code.push_str(&format!("% Push parameter '{param_name}'\n"));
-            let c_reg = if is_reg(&child_label) {
-                push_code.push_str(&format!("sw {offset}(r14), {child_label}\n"));
-                child_label
+            let c_reg = if child_type.dimensions().is_empty() {
+                self.load_label(&mut code, &child_ref, child_label)?
             } else {
-                let reg = self.get_register();
-                code.push_str(&format!("lw {reg}, {child_label}\n"));
-                push_code.push_str(&format!("sw {offset}(r14), {reg}\n"));
-                reg
+                self.address_of(&mut code, &child_ref, child_label)?
             };
-            self.free_register(c_reg);
+            push_code.push_str(&format!("sw {offset}(r14), {c_reg}\n"));
+            pushed_regs.push(c_reg);
 
             offset -= param_size as isize;
         }
 
+        // self comes right after the parameters
+        if let Some(object_label) = object_label {
+            let (object_offset, base_reg) = split_label(&object_label).ok_or_else(|| {
+                CompilerError::new_with_node(
+                    format!("Cannot call '{target}', the object isn't stored anywhere!"),
+                    &node_ref,
+                )
+            })?;
+
+            code.push_str(&object_code.unwrap_or_default());
+            let s_reg = self.get_register(&node_ref)?;
+            code.push_str(&format!("addi {s_reg}, {base_reg}, {object_offset}\n"));
+            self.free_register(base_reg);
+
+            push_code.push_str("% Push self\n");
+            push_code.push_str(&format!("sw {offset}(r14), {s_reg}\n"));
+            pushed_regs.push(s_reg);
+        }
+
+        pushed_regs
+            .into_iter()
+            .for_each(|reg| self.free_register(reg));
+
         code.push_str(&format!("addi r14, r14, -{us_size}\n"));
         code.push_str(&push_code);
 
@@ -923,16 +1340,7 @@ impl Visitor for CodegenVisitor {
         code.push_str("% Return stack pointer\n");
         code.push_str(&format!("addi r14, r14, {us_size}\n"));
 
-        // return is always stored in 13
-        let ret_reg = self.get_register();
-        code.push_str("% Store return value\n");
-        code.push_str(&format!("add {ret_reg}, r0, r13\n"));
-
-        code.push_str(&format!("% End function call {func_signature}\n\n"));
-
-        node_ref.code.borrow_mut().replace(code);
-        node_ref.label.borrow_mut().replace(ret_reg);
-        Ok(())
+        Ok(code)
     }
 }
 
@@ -956,3 +1364,9 @@ fn get_literal_label(lit_type: &Type) -> (String, usize, String, Vec<u8>) {
 fn is_reg(string: &str) -> bool {
     string.starts_with('r')
 }
+
+/// Split a memory label like `-8(r14)` into its offset and base register
+fn split_label(label: &str) -> Option<(isize, String)> {
+    let (offset, base) = label.strip_suffix(')')?.split_once('(')?;
+    Some((offset.parse().ok()?, base.to_string()))
+}
@@ -1,12 +1,87 @@
 use std::fmt::{Display, Formatter};
 
-use crate::lexical::tokens::{location::Location, token::Token};
+use crate::{
+    ast::nodes::StructNode,
+    lexical::tokens::{location::Location, span::Span, token::Token},
+};
+
+/// How bad a diagnostic is, only errors stop the compiler from generating code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
+    /// Extra context for another diagnostic, like where something was declared
+    Note,
+    /// A suggestion on how to fix another diagnostic
+    Help,
+}
+
+impl Severity {
+    /// What's shown before the message, errors don't get one
+    fn prefix(&self) -> &'static str {
+        match self {
+            Severity::Error => "",
+            Severity::Warning => "[WARN] ",
+            Severity::Note => "[NOTE] ",
+            Severity::Help => "[HELP] ",
+        }
+    }
+}
+
+/// The kinds of warnings, so they can be allowed one kind at a time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// An attribute with the same name as an inherited one
+    Shadowing,
+    /// A member function overriding an inherited one with a different signature
+    Override,
+    /// A local that may be read before it's assigned
+    Uninitialized,
+    /// Statements after a return
+    Unreachable,
+    /// Symbols that are declared but never used
+    Unused,
+}
+
+impl WarningKind {
+    pub const ALL: [WarningKind; 5] = [
+        WarningKind::Shadowing,
+        WarningKind::Override,
+        WarningKind::Uninitialized,
+        WarningKind::Unreachable,
+        WarningKind::Unused,
+    ];
+
+    /// The name used by driver flags, like `--allow=unused`
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarningKind::Shadowing => "shadowing",
+            WarningKind::Override => "override",
+            WarningKind::Uninitialized => "uninitialized",
+            WarningKind::Unreachable => "unreachable",
+            WarningKind::Unused => "unused",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|k| k.name() == name)
+    }
+}
 
 #[derive(Debug, Clone, Eq)]
 pub struct CompilerError {
     pub message: String,
     pub location: Location,
+    pub span: Option<Span>,
     pub token: Option<Token>,
+    pub severity: Severity,
+    /// Only set for warnings
+    pub kind: Option<WarningKind>,
+    /// Stable code from `diagnostic_codes`, internal errors don't have one
+    pub code: Option<&'static str>,
+    /// Notes (or help) about related places in the source, shown right after this
+    pub notes: Vec<CompilerError>,
 }
 
 impl CompilerError {
@@ -14,7 +89,12 @@ impl CompilerError {
         Self {
             message,
             location: token.location.clone(),
+            span: Some(token.span()),
             token: Some(token),
+            severity: Severity::Error,
+            kind: None,
+            code: None,
+            notes: vec![],
         }
     }
 
@@ -22,20 +102,207 @@ impl CompilerError {
         Self {
             message,
             location: Location::new(0, 0),
+            span: None,
             token: None,
+            severity: Severity::Error,
+            kind: None,
+            code: None,
+            notes: vec![],
+        }
+    }
+
+    /// Create an error covering the whole node, instead of just its token
+    pub fn new_with_node(message: String, node: &StructNode) -> Self {
+        Self {
+            message,
+            location: node.span.start.clone(),
+            span: Some(node.span.clone()),
+            token: Some(node.token.clone()),
+            severity: Severity::Error,
+            kind: None,
+            code: None,
+            notes: vec![],
+        }
+    }
+
+    /// Turn the error into a warning of the given kind
+    pub fn warning(mut self, kind: WarningKind) -> Self {
+        self.severity = Severity::Warning;
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Give the diagnostic its code from `diagnostic_codes`, ie. `E0203`
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Attach a note pointing at a related node
+    pub fn with_note(mut self, message: String, node: &StructNode) -> Self {
+        let mut note = Self::new_with_node(message, node);
+        note.severity = Severity::Note;
+        self.notes.push(note);
+        self
+    }
+
+    /// Attach a suggestion, without a location of its own
+    pub fn with_help(mut self, message: String) -> Self {
+        let mut help = Self::new_with_message(message);
+        help.severity = Severity::Help;
+        self.notes.push(help);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Format the error along with the source lines it spans, underlining the offending part
+    ///
+    /// ```text
+    /// [line 6]:  Cannot do arithmetic on mixed types (Tried Integer & Float)!
+    ///     6 |   x = x + y;
+    ///       |       ^^^^^
+    /// ```
+    pub fn fmt_with_source(&self, source: &str) -> String {
+        let mut output = self.fmt_line();
+
+        if let Some(span) = self.span.as_ref().filter(|s| s.start.line > 0) {
+            output.push_str(&fmt_span(span, source));
+        }
+
+        for note in &self.notes {
+            output.push('\n');
+            output.push_str(&note.fmt_with_source(source));
+        }
+
+        output
+    }
+
+    /// Just the location and message, without the notes
+    fn fmt_line(&self) -> String {
+        // Help has no location of its own, it's about the diagnostic above it
+        if self.severity == Severity::Help && self.span.is_none() {
+            return format!("    {}{}", self.severity.prefix(), self.message);
         }
+
+        let location = match &self.token {
+            Some(t) => &t.location,
+            None => &self.location,
+        };
+
+        let code = self.code.map(|c| format!("[{c}] ")).unwrap_or_default();
+
+        format!(
+            "{location}:  {}{code}{}",
+            self.severity.prefix(),
+            self.message
+        )
+    }
+}
+
+/// The source lines a span covers, underlining the spanned part
+fn fmt_span(span: &Span, source: &str) -> String {
+    let mut output = String::new();
+    let gutter = span.end.line.to_string().len();
+
+    for (i, line) in source
+        .lines()
+        .enumerate()
+        .skip(span.start.line - 1)
+        .take(span.end.line - span.start.line + 1)
+    {
+        let line_number = i + 1;
+        let start = if line_number == span.start.line {
+            span.start.column
+        } else {
+            1
+        };
+        let end = if line_number == span.end.line {
+            span.end.column
+        } else {
+            line.chars().count() + 1
+        };
+
+        // Keep tabs as tabs so the carets line up however wide the terminal draws them
+        let padding: String = line
+            .chars()
+            .take(start.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(end.saturating_sub(start).max(1));
+
+        output.push_str(&format!("\n    {line_number:>gutter$} | {line}"));
+        output.push_str(&format!("\n    {:>gutter$} | {padding}{carets}", ""));
     }
+
+    output
 }
 
 impl Display for CompilerError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let first = if let Some(t) = &self.token {
-            format!("{}: ", t.location)
-        } else {
-            format!("{}: ", self.location)
+        write!(f, "{}", self.fmt_line())?;
+
+        for note in &self.notes {
+            write!(f, "\n{note}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// What to do with diagnostics before they're reported, set by driver flags
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticOptions {
+    /// Report warnings as errors, so they stop codegen too
+    pub warnings_as_errors: bool,
+    /// Kinds of warnings that aren't reported at all
+    pub allowed: Vec<WarningKind>,
+    /// Only report this many errors, warnings don't count
+    pub max_errors: Option<usize>,
+}
+
+impl DiagnosticOptions {
+    /// Drop allowed warnings and promote the rest if asked to, as they're reported
+    pub fn filter(&self, diagnostics: Vec<CompilerError>) -> Vec<CompilerError> {
+        diagnostics
+            .into_iter()
+            .filter(|d| !d.kind.is_some_and(|k| self.allowed.contains(&k)))
+            .map(|mut d| {
+                if self.warnings_as_errors && d.severity == Severity::Warning {
+                    d.severity = Severity::Error;
+                }
+                d
+            })
+            .collect()
+    }
+
+    /// The diagnostics to show sorted by location, without the errors past `max_errors`, and how
+    /// many errors were left out
+    pub fn limit(&self, diagnostics: &[CompilerError]) -> (Vec<CompilerError>, usize) {
+        let mut sorted = diagnostics.to_vec();
+        sorted.sort();
+
+        let max_errors = match self.max_errors {
+            Some(max_errors) => max_errors,
+            None => return (sorted, 0),
         };
 
-        write!(f, "{} {}", first, self.message)
+        let mut shown = vec![];
+        let mut errors = 0;
+
+        for diagnostic in sorted {
+            if diagnostic.is_error() {
+                errors += 1;
+                if errors > max_errors {
+                    continue;
+                }
+            }
+            shown.push(diagnostic);
+        }
+
+        (shown, errors.saturating_sub(max_errors))
     }
 }
 
@@ -49,6 +316,17 @@ pub fn print_errors(errors: &[CompilerError]) {
     }
 }
 
+/// Like `print_errors`, but underlines where each error is in the source
+pub fn print_errors_with_source(errors: &[CompilerError], source: &str) {
+    let mut e = errors.to_vec();
+    // sort them by location
+    e.sort();
+
+    for error in e {
+        eprintln!("{}", error.fmt_with_source(source));
+    }
+}
+
 pub fn errors_to_string(errors: &[CompilerError]) -> String {
     let mut e = errors.to_vec();
     // sort them by location
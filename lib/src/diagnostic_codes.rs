@@ -0,0 +1,585 @@
+//! Stable codes for every kind of diagnostic the semantic passes report
+//!
+//! Codes never change meaning once they're published, so course material and CI filters can rely
+//! on them. Errors are `E` codes and warnings `W` codes, grouped by hundreds:
+//!
+//! - `x01xx`: declarations, definitions and inheritance
+//! - `x02xx`: resolving names, members and calls (and symbols nothing uses)
+//! - `x03xx`: types
+//! - `x04xx`: control flow
+//!
+//! Diagnostics without a code are internal errors (ie. a malformed tree), they're bugs in the
+//! compiler rather than in the program being compiled.
+
+/// Everything there is to know about a diagnostic code, for `--explain`
+#[derive(Debug)]
+pub struct DiagnosticInfo {
+    pub code: &'static str,
+    /// Short description, like the message without the specifics
+    pub title: &'static str,
+    pub explanation: &'static str,
+    /// A minimal program that reports the diagnostic
+    pub example: &'static str,
+}
+
+impl DiagnosticInfo {
+    /// The long-form explanation, as printed by `--explain`
+    pub fn explain(&self) -> String {
+        let example = self
+            .example
+            .lines()
+            .map(|l| format!("    {l}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        format!(
+            "{}: {}\n\n{}\n\nExample:\n\n{example}\n",
+            self.code, self.title, self.explanation
+        )
+    }
+}
+
+/// Look up a code, ie. `E0203`
+pub fn lookup(code: &str) -> Option<&'static DiagnosticInfo> {
+    REGISTRY.iter().find(|info| info.code == code)
+}
+
+pub static REGISTRY: &[DiagnosticInfo] = &[
+    DiagnosticInfo {
+        code: "E0101",
+        title: "Class defined more than once",
+        explanation: "Every class name has to be unique. Only the first definition is used, \
+            rename or remove the others.",
+        example: "class A {};
+class A {};
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0102",
+        title: "Function defined more than once",
+        explanation: "A function with the same name and parameter types is defined twice. \
+            Functions can share a name (overloads) as long as their parameter types differ.",
+        example: "function f(x: integer) => void {}
+function f(y: integer) => void {}
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0103",
+        title: "Variable defined more than once",
+        explanation: "A local variable or parameter has the same name as another one in the same \
+            function.",
+        example: "function main() => void {
+  localvar x: integer;
+  localvar x: float;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0104",
+        title: "Duplicate class member",
+        explanation: "Two members of a class are declared with the same name: two attributes, an \
+            attribute and a member function, or two member functions (or constructors) with the \
+            same parameter types. Member functions and constructors can be overloaded, they can \
+            share a name as long as their parameter types differ.",
+        example: "class A {
+  public attribute x: integer;
+  public attribute x: float;
+};
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0105",
+        title: "No main function",
+        explanation: "Every program needs a `function main() => void`, it's where execution \
+            starts. It can't take any parameters.",
+        example: "function start() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0106",
+        title: "Unknown base class",
+        explanation: "A class inherits (`isa`) from a class that isn't defined anywhere.",
+        example: "class A isa B {};
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0107",
+        title: "Circular inheritance",
+        explanation: "A class inherits from itself, directly or through other classes. \
+            Inheritance has to form a tree.",
+        example: "class A isa B {};
+class B isa A {};
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0108",
+        title: "Member function defined for an unknown class",
+        explanation: "`function A::f()` defines a member function of class `A`, which has to \
+            be declared with `class A { ... };`.",
+        example: "function A::f() => void {}
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0109",
+        title: "Member function defined but never declared",
+        explanation: "Member functions have to be declared in their class before they're \
+            defined with `function A::f()`.",
+        example: "class A {};
+
+function A::f() => void {}
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0110",
+        title: "Member function declared but never defined",
+        explanation: "Every member function declared in a class needs a definition \
+            (`function A::f() { ... }`) with the same parameter and return types.",
+        example: "class A {
+  public function f: () => void;
+};
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0111",
+        title: "Member function definition has the wrong return type",
+        explanation: "A member function definition takes the same parameters as its declaration, \
+            but returns a different type.",
+        example: "class A {
+  public function f: () => integer;
+};
+
+function A::f() => float {
+  return (1.5);
+}
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0112",
+        title: "Member function definition has the wrong parameters",
+        explanation: "A member function definition doesn't take the same parameter types as any \
+            declaration with its name.",
+        example: "class A {
+  public function f: (x: integer) => void;
+};
+
+function A::f(x: float) => void {}
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0113",
+        title: "Array without a size",
+        explanation: "Only parameters can be declared as arrays without a size (`integer[]`), \
+            since they take the size of whatever is passed. Every other array needs one.",
+        example: "function main() => void {
+  localvar a: integer[];
+}",
+    },
+    DiagnosticInfo {
+        code: "E0114",
+        title: "Class contains itself",
+        explanation: "An object holds its attributes directly, so a class can't have an \
+            attribute of its own type, directly or through the attributes of other classes. \
+            Its objects would have to be infinitely big.",
+        example: "class A {
+  public attribute b: B;
+};
+class B {
+  public attribute a: A;
+};
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "E0201",
+        title: "Unknown variable",
+        explanation: "The variable isn't a local, parameter, or (inside member functions) an \
+            attribute of `self`. Variables have to be declared with `localvar` before they're \
+            used.",
+        example: "function main() => void {
+  x = 1;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0202",
+        title: "Unknown class",
+        explanation: "A type refers to a class that isn't defined anywhere.",
+        example: "function main() => void {
+  localvar a: A;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0203",
+        title: "Function not found",
+        explanation: "There's no function (or member function) with that name. The message shows \
+            the types of the arguments it was called with.",
+        example: "function main() => void {
+  write(f(1));
+}",
+    },
+    DiagnosticInfo {
+        code: "E0204",
+        title: "No matching overload",
+        explanation: "Functions with that name exist, but none of them takes the types of the \
+            arguments. The message lists every candidate. Objects declared with arguments \
+            (`localvar a: A(1);`) are checked against the constructors their class declares.",
+        example: "function f(x: integer) => integer {
+  return (x);
+}
+
+function main() => void {
+  write(f(1.5));
+}",
+    },
+    DiagnosticInfo {
+        code: "E0205",
+        title: "Ambiguous call",
+        explanation: "More than one overload matches the arguments equally well. Exact matches \
+            win over arrays passed to unsized (`integer[]`) parameters, so this only happens \
+            when the candidates need as many of those.",
+        example: "function f(x: integer[], y: integer[2]) => void {}
+function f(x: integer[2], y: integer[]) => void {}
+
+function main() => void {
+  localvar a: integer[2];
+  f(a, a);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0206",
+        title: "Unknown class member",
+        explanation: "The class (and the classes it inherits from) has no attribute or member \
+            function with that name.",
+        example: "class A {
+  public attribute x: integer;
+};
+
+function main() => void {
+  localvar a: A;
+  a.y = 1;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0207",
+        title: "`self` outside a member function",
+        explanation: "`self` is the object a member function is called on, free functions don't \
+            have one.",
+        example: "function main() => void {
+  write(self.x);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0208",
+        title: "Constructor arguments for a class without constructors",
+        explanation: "An object is declared with arguments (`localvar a: A(1);`), but its class \
+            doesn't declare any constructor to pass them to.",
+        example: "class A {
+  public attribute x: integer;
+};
+
+function main() => void {
+  localvar a: A(1);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0209",
+        title: "Private member accessed outside its class",
+        explanation: "Private members (and constructors) are only accessible inside the member \
+            functions of the class declaring them, not even in classes inheriting them.",
+        example: "class A {
+  private attribute x: integer;
+};
+
+function main() => void {
+  localvar a: A;
+  write(a.x);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0210",
+        title: "Member function used as an attribute",
+        explanation: "Member functions have to be called, with parentheses.",
+        example: "class A {
+  public function f: () => integer;
+};
+
+function A::f() => integer {
+  return (1);
+}
+
+function main() => void {
+  localvar a: A;
+  write(a.f);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0211",
+        title: "Attribute called as a member function",
+        explanation: "Only member functions can be called, attributes are used without \
+            parentheses.",
+        example: "class A {
+  public attribute f: integer;
+};
+
+function main() => void {
+  localvar a: A;
+  write(a.f());
+}",
+    },
+    DiagnosticInfo {
+        code: "E0212",
+        title: "Member access on a value that isn't an object",
+        explanation: "The `.` operator only works on objects, not integers, floats or arrays of \
+            them.",
+        example: "function main() => void {
+  localvar x: integer;
+  write(x.y);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0213",
+        title: "Member access on an array of objects",
+        explanation: "An array of objects doesn't have members itself, index it to get one of \
+            its objects first (`a[0].x`).",
+        example: "class A {
+  public attribute x: integer;
+};
+
+function main() => void {
+  localvar a: A[2];
+  write(a.x);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0214",
+        title: "Misused `self`",
+        explanation: "`self` is the object a member function is called on. It can be used like a \
+            variable (`self.x`, `self.f()`), but it can't be the name of anything (a variable, \
+            a parameter, a member) and it can't be assigned to.",
+        example: "function main() => void {
+  localvar self: integer;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0301",
+        title: "Assignment of the wrong type",
+        explanation: "The expression assigned to a variable has a different type than the \
+            variable. There are no implicit conversions, not even from `integer` to `float`.",
+        example: "function main() => void {
+  localvar x: integer;
+  x = 1.5;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0302",
+        title: "Operands of different types",
+        explanation: "Both sides of an arithmetic or relational operator need the same type, \
+            there are no implicit conversions.",
+        example: "function main() => void {
+  write(1 + 1.5);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0303",
+        title: "Operand of the wrong type",
+        explanation: "Arithmetic and relational operators only work on `integer` and `float` \
+            values, and `and`, `or` and `not` only on `integer` ones.",
+        example: "function main() => void {
+  write(not 1.5);
+}",
+    },
+    DiagnosticInfo {
+        code: "E0304",
+        title: "Returned value has the wrong type",
+        explanation: "A `return` gives a value of a different type than the function's return \
+            type.",
+        example: "function f() => integer {
+  return (1.5);
+}
+
+function main() => void {
+  write(f());
+}",
+    },
+    DiagnosticInfo {
+        code: "E0305",
+        title: "Array index isn't an integer",
+        explanation: "Arrays can only be indexed with `integer` expressions.",
+        example: "function main() => void {
+  localvar a: integer[2];
+  a[1.5] = 1;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0306",
+        title: "Too many array indices",
+        explanation: "An array is indexed with more indices than it has dimensions.",
+        example: "function main() => void {
+  localvar a: integer[2];
+  a[1][1] = 1;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0307",
+        title: "Array index out of bounds",
+        explanation: "An array is indexed with a constant (an expression made only of literals) \
+            that's negative or not less than the size of that dimension. Indices start at 0, so \
+            the last element of `integer[3]` is at index 2.",
+        example: "function main() => void {
+  localvar a: integer[3];
+  a[1 + 2] = 1;
+}",
+    },
+    DiagnosticInfo {
+        code: "E0309",
+        title: "Division by zero",
+        explanation: "Something is divided by a constant (an expression made only of literals) \
+            that's zero, which can't be computed when the program runs either.",
+        example: "function main() => void {
+  localvar x: integer;
+  x = 1;
+  write(x / (2 - 2));
+}",
+    },
+    DiagnosticInfo {
+        code: "E0401",
+        title: "Missing return",
+        explanation: "A function with a return type can reach the end of its body without a \
+            `return`. Both branches of an `if` have to return for the `if` to count, and a \
+            `while` loop might not run at all.",
+        example: "function f(x: integer) => integer {
+  if (x > 0) then {
+    return (1);
+  } else;
+}
+
+function main() => void {
+  write(f(1));
+}",
+    },
+    DiagnosticInfo {
+        code: "E0402",
+        title: "Value returned from a void function",
+        explanation: "`void` functions and constructors don't return anything, so they can't \
+            use `return`.",
+        example: "function main() => void {
+  return (1);
+}",
+    },
+    DiagnosticInfo {
+        code: "W0101",
+        title: "Attribute shadows an inherited attribute",
+        explanation: "A class declares an attribute with the same name as one it inherits. Both \
+            exist in the object, but only the new one can be accessed by name.",
+        example: "class A {
+  public attribute x: integer;
+};
+
+class B isa A {
+  public attribute x: integer;
+};
+
+function main() => void {
+  localvar b: B;
+}",
+    },
+    DiagnosticInfo {
+        code: "W0102",
+        title: "Override with a different signature",
+        explanation: "A class declares a member function with the same name as an inherited \
+            one, but different parameter or return types.",
+        example: "class A {
+  public function f: (x: integer) => void;
+};
+
+class B isa A {
+  public function f: (x: float) => void;
+};
+
+function A::f(x: integer) => void {
+  write(x);
+}
+
+function B::f(x: float) => void {
+  write(x);
+}
+
+function main() => void {
+  localvar b: B;
+}",
+    },
+    DiagnosticInfo {
+        code: "W0201",
+        title: "Unused local variable",
+        explanation: "A local variable is declared but its value is never read, assigning to it \
+            doesn't count. Objects built with a constructor are always used, since the \
+            constructor runs. End its name with `_` if that's intended.",
+        example: "function main() => void {
+  localvar x: integer;
+}",
+    },
+    DiagnosticInfo {
+        code: "W0202",
+        title: "Unused parameter",
+        explanation: "A parameter's value is never read in its function, assigning to it \
+            doesn't count. End its name with `_` if that's intended.",
+        example: "function f(x: integer) => void {
+  write(1);
+}
+
+function main() => void {
+  f(1);
+}",
+    },
+    DiagnosticInfo {
+        code: "W0203",
+        title: "Function never called",
+        explanation: "A free function isn't called from `main`, even through other functions. \
+            End its name with `_` if that's intended.",
+        example: "function f() => void {}
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "W0204",
+        title: "Class never instantiated",
+        explanation: "No object of the class is ever declared, and no instantiated class \
+            inherits from it or has it as an attribute. End its name with `_` if that's \
+            intended.",
+        example: "class A {};
+
+function main() => void {}",
+    },
+    DiagnosticInfo {
+        code: "W0401",
+        title: "Variable may be used before it's assigned",
+        explanation: "A local `integer` or `float` is read on a path where it isn't assigned yet, \
+            so its value is whatever was left in memory. Assign it on every path before reading \
+            it.",
+        example: "function main() => void {
+  localvar x: integer;
+  write(x);
+}",
+    },
+    DiagnosticInfo {
+        code: "W0402",
+        title: "Unreachable statement",
+        explanation: "The statement comes after a `return` (or an `if` returning in both \
+            branches), so it never runs.",
+        example: "function f() => integer {
+  return (1);
+  write(2);
+}
+
+function main() => void {
+  write(f());
+}",
+    },
+];
@@ -1,4 +1,5 @@
 pub mod error_type;
 pub mod location;
+pub mod span;
 pub mod token;
 pub mod token_type;
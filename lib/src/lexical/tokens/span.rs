@@ -0,0 +1,44 @@
+use std::fmt::Display;
+
+use super::location::Location;
+
+/// A range in the source, from the start of one token to the end of another
+///
+/// `end` is exclusive, so a token `abc` at column 5 spans columns 5 to 8
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+impl Span {
+    pub fn new(start: Location, end: Location) -> Span {
+        Span { start, end }
+    }
+
+    /// Creates a span that covers both spans (and anything between them)
+    pub fn merge(&self, other: &Span) -> Span {
+        Span {
+            start: self.start.clone().min(other.start.clone()),
+            end: self.end.clone().max(other.end.clone()),
+        }
+    }
+
+    pub fn contains(&self, location: &Location) -> bool {
+        self.start <= *location && *location < self.end
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        self.start.line != self.end.line
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_multiline() {
+            write!(f, "[lines {}-{}]", self.start.line, self.end.line)
+        } else {
+            write!(f, "{}", self.start)
+        }
+    }
+}
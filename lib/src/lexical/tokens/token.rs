@@ -1,6 +1,6 @@
 use std::fmt;
 
-use super::{location::Location, token_type::Type};
+use super::{location::Location, span::Span, token_type::Type};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Token {
@@ -25,6 +25,22 @@ impl Token {
             location: Location::new(0, 0),
         }
     }
+
+    /// The span of source covered by this token's lexeme
+    pub fn span(&self) -> Span {
+        let mut end = self.location.clone();
+
+        for c in self.lexeme.chars() {
+            if c == '\n' {
+                end.line += 1;
+                end.column = 1;
+            } else {
+                end.column += 1;
+            }
+        }
+
+        Span::new(self.location.clone(), end)
+    }
 }
 
 impl fmt::Display for Token {
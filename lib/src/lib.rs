@@ -1,6 +1,8 @@
 pub mod ast;
 pub mod codegen;
 pub mod compiler_error;
+pub mod diagnostic_codes;
 pub mod lexical;
+pub mod passes;
 pub mod semantic;
 pub mod syntactic;
@@ -0,0 +1,325 @@
+use crate::{
+    ast::{nodes::CodeNode, symbol_table::SymbolTable},
+    codegen::codegen_visitor::CodegenVisitor,
+    semantic::{
+        class_layout::{ClassLayoutVisitor, ClassLayouts},
+        constants::ConstantFolder,
+        control_flow::ControlFlowChecker,
+        definite_assignment::DefiniteAssignmentChecker,
+        folder::Folder,
+        inheritance::InheritanceVisitor,
+        member_definitions::MemberDefinitionVisitor,
+        symbol_collector::SymbolCollectorVisitor,
+        symbol_globals::SymbolGlobalResolverVisitor,
+        symbol_visitor::SymbolTableVisitor,
+        unused_symbols::UnusedSymbolChecker,
+        visitor::{Visitor, VisitorResult},
+        xref::{SymbolReference, XrefIndex},
+    },
+};
+
+use super::pass::{Pass, PassContext, PassStage};
+
+/// Classes, members and function signatures are in the tree's symbol tables (no value)
+pub const GLOBAL_SYMBOLS: &str = "global_symbols";
+/// Base classes exist and don't form cycles (no value)
+pub const INHERITANCE: &str = "inheritance";
+/// The size and attribute offsets of every class, as `ClassLayouts`
+pub const CLASS_LAYOUT: &str = "class_layout";
+/// Locals and parameters are in the tree's symbol tables and expressions are typed (no value)
+pub const LOCAL_SYMBOLS: &str = "local_symbols";
+/// What each variable, member and call resolved to, as `Vec<SymbolReference>`
+pub const SYMBOL_REFERENCES: &str = "symbol_references";
+/// The complete global `SymbolTable`
+pub const SYMBOL_TABLE: &str = "symbol_table";
+/// Constant expressions in the tree are replaced by their value (no value)
+pub const FOLDED_CONSTANTS: &str = "folded_constants";
+/// Definitions and references of every symbol, as an `XrefIndex`
+pub const XREF: &str = "xref";
+/// The generated MOON code, as a `String`
+pub const MOON_CODE: &str = "moon_code";
+
+pub struct SymbolGlobalsPass;
+
+impl Pass for SymbolGlobalsPass {
+    fn name(&self) -> &'static str {
+        "symbol_globals"
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![GLOBAL_SYMBOLS]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let res = SymbolGlobalResolverVisitor::new().visit(root);
+        context.set_output(GLOBAL_SYMBOLS, ());
+        res
+    }
+}
+
+pub struct InheritancePass;
+
+impl Pass for InheritancePass {
+    fn name(&self) -> &'static str {
+        "inheritance"
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![GLOBAL_SYMBOLS]
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![INHERITANCE]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let res = InheritanceVisitor::new().visit(root);
+        context.set_output(INHERITANCE, ());
+        res
+    }
+}
+
+pub struct MemberDefinitionsPass;
+
+impl Pass for MemberDefinitionsPass {
+    fn name(&self) -> &'static str {
+        "member_definitions"
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![GLOBAL_SYMBOLS]
+    }
+
+    fn run(&self, root: &CodeNode, _context: &mut PassContext) -> VisitorResult {
+        MemberDefinitionVisitor::new().visit(root)
+    }
+}
+
+pub struct ClassLayoutPass;
+
+impl Pass for ClassLayoutPass {
+    fn name(&self) -> &'static str {
+        "class_layout"
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![GLOBAL_SYMBOLS, INHERITANCE]
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![CLASS_LAYOUT]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let mut layout_visitor = ClassLayoutVisitor::new();
+        let res = layout_visitor.visit(root);
+        context.set_output(CLASS_LAYOUT, layout_visitor.layouts);
+        res
+    }
+}
+
+pub struct SymbolTablePass;
+
+impl Pass for SymbolTablePass {
+    fn name(&self) -> &'static str {
+        "symbol_tables"
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![GLOBAL_SYMBOLS, CLASS_LAYOUT]
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![LOCAL_SYMBOLS, SYMBOL_REFERENCES]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let mut symbol_visitor = SymbolTableVisitor::new();
+        let res = symbol_visitor.visit(root);
+        context.set_output(LOCAL_SYMBOLS, ());
+        context.set_output(SYMBOL_REFERENCES, symbol_visitor.references);
+        res
+    }
+}
+
+pub struct SymbolCollectorPass;
+
+impl Pass for SymbolCollectorPass {
+    fn name(&self) -> &'static str {
+        "symbol_collector"
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![LOCAL_SYMBOLS]
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![SYMBOL_TABLE]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let mut collector = SymbolCollectorVisitor::new();
+        let res = collector.visit(root);
+        context.set_output(SYMBOL_TABLE, collector.global);
+        res
+    }
+}
+
+pub struct XrefPass;
+
+impl Pass for XrefPass {
+    fn name(&self) -> &'static str {
+        "xref"
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![SYMBOL_TABLE, SYMBOL_REFERENCES]
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![XREF]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let index = match (
+            context.output::<SymbolTable>(SYMBOL_TABLE),
+            context.output::<Vec<SymbolReference>>(SYMBOL_REFERENCES),
+        ) {
+            (Some(global), Some(references)) => XrefIndex::build(root, global, references),
+            _ => return Ok(()),
+        };
+
+        context.set_output(XREF, index);
+        Ok(())
+    }
+}
+
+pub struct ControlFlowPass;
+
+impl Pass for ControlFlowPass {
+    fn name(&self) -> &'static str {
+        "control_flow"
+    }
+
+    fn stage(&self) -> PassStage {
+        PassStage::Lint
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![LOCAL_SYMBOLS]
+    }
+
+    fn run(&self, root: &CodeNode, _context: &mut PassContext) -> VisitorResult {
+        ControlFlowChecker::new().check(root)
+    }
+}
+
+pub struct DefiniteAssignmentPass;
+
+impl Pass for DefiniteAssignmentPass {
+    fn name(&self) -> &'static str {
+        "definite_assignment"
+    }
+
+    fn stage(&self) -> PassStage {
+        PassStage::Lint
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![LOCAL_SYMBOLS]
+    }
+
+    fn run(&self, root: &CodeNode, _context: &mut PassContext) -> VisitorResult {
+        DefiniteAssignmentChecker::new().check(root)
+    }
+}
+
+pub struct UnusedSymbolsPass;
+
+impl Pass for UnusedSymbolsPass {
+    fn name(&self) -> &'static str {
+        "unused_symbols"
+    }
+
+    fn stage(&self) -> PassStage {
+        PassStage::Lint
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![SYMBOL_TABLE, SYMBOL_REFERENCES]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        match (
+            context.output::<SymbolTable>(SYMBOL_TABLE),
+            context.output::<Vec<SymbolReference>>(SYMBOL_REFERENCES),
+        ) {
+            (Some(global), Some(references)) => {
+                UnusedSymbolChecker::new(global, references).check(root)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct ConstantFoldingPass;
+
+impl Pass for ConstantFoldingPass {
+    fn name(&self) -> &'static str {
+        "constant_folding"
+    }
+
+    fn stage(&self) -> PassStage {
+        PassStage::Optimisation
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![LOCAL_SYMBOLS]
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![FOLDED_CONSTANTS]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let res = ConstantFolder.fold(root).map(|_| ());
+        context.set_output(FOLDED_CONSTANTS, ());
+        res
+    }
+}
+
+pub struct CodegenPass;
+
+impl Pass for CodegenPass {
+    fn name(&self) -> &'static str {
+        "codegen"
+    }
+
+    fn stage(&self) -> PassStage {
+        PassStage::Codegen
+    }
+
+    fn requires(&self) -> Vec<&'static str> {
+        vec![SYMBOL_TABLE, CLASS_LAYOUT, FOLDED_CONSTANTS]
+    }
+
+    fn provides(&self) -> Vec<&'static str> {
+        vec![MOON_CODE]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult {
+        let (code, res) = match (
+            context.output::<SymbolTable>(SYMBOL_TABLE),
+            context.output::<ClassLayouts>(CLASS_LAYOUT),
+        ) {
+            (Some(global), Some(layouts)) => {
+                let mut codegen_visitor = CodegenVisitor::new(global, layouts);
+                let res = codegen_visitor.visit(root);
+                (codegen_visitor.get_code().trim().to_string(), res)
+            }
+            _ => return Ok(()),
+        };
+        context.set_output(MOON_CODE, code);
+        res
+    }
+}
@@ -0,0 +1,3 @@
+pub mod builtin;
+pub mod pass;
+pub mod pass_manager;
@@ -0,0 +1,94 @@
+use std::{any::Any, collections::HashMap};
+
+use crate::{
+    ast::nodes::CodeNode, compiler_error::CompilerError, semantic::visitor::VisitorResult,
+};
+
+/// Where in the pipeline a pass belongs, passes run in this order unless their dependencies say otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PassStage {
+    /// Builds or checks semantic info (symbol tables, types, ...)
+    Analysis,
+    /// Only reports problems, never changes the tree
+    Lint,
+    /// Rewrites the tree, expects it to be valid
+    Optimisation,
+    /// Produces output, expects the tree to be valid
+    Codegen,
+}
+
+impl PassStage {
+    /// Optimisations and codegen can't do anything useful with a tree that failed to check
+    pub fn needs_valid_input(&self) -> bool {
+        matches!(self, PassStage::Optimisation | PassStage::Codegen)
+    }
+}
+
+/// A step of the compiler pipeline, registered with a `PassManager`
+///
+/// Passes are ordered by the outputs they `require` and `provide`, so a pass that needs the
+/// final symbol table just requires `"symbol_table"` instead of naming whatever builds it.
+pub trait Pass {
+    /// Unique name of the pass, used in messages and to skip it
+    fn name(&self) -> &'static str;
+
+    fn stage(&self) -> PassStage {
+        PassStage::Analysis
+    }
+
+    /// Outputs (from other passes) that must exist before this pass runs
+    fn requires(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    /// Outputs this pass stores in the context
+    fn provides(&self) -> Vec<&'static str> {
+        vec![]
+    }
+
+    fn run(&self, root: &CodeNode, context: &mut PassContext) -> VisitorResult;
+}
+
+/// Shared state between passes: whatever they output, and every error reported so far
+#[derive(Default)]
+pub struct PassContext {
+    outputs: HashMap<&'static str, Box<dyn Any>>,
+    /// Every diagnostic reported so far, including warnings
+    pub errors: Vec<CompilerError>,
+    /// Names of passes that ran, in order
+    pub ran: Vec<&'static str>,
+    /// Names of passes skipped because of earlier errors
+    pub skipped: Vec<&'static str>,
+}
+
+impl PassContext {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Start with some errors already reported, ie. from parsing
+    pub fn new_with_errors(errors: Vec<CompilerError>) -> Self {
+        Self {
+            errors,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_output<T: Any>(&mut self, name: &'static str, value: T) {
+        self.outputs.insert(name, Box::new(value));
+    }
+
+    /// Get an output, if a pass provided it and it has the type `T`
+    pub fn output<T: Any>(&self, name: &str) -> Option<&T> {
+        self.outputs.get(name).and_then(|o| o.downcast_ref())
+    }
+
+    pub fn has_output(&self, name: &str) -> bool {
+        self.outputs.contains_key(name)
+    }
+
+    /// Whether anything reported so far is an error, warnings don't count
+    pub fn has_errors(&self) -> bool {
+        self.errors.iter().any(|e| e.is_error())
+    }
+}
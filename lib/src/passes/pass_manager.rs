@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::nodes::CodeNode,
+    compiler_error::{CompilerError, CompilerResult, DiagnosticOptions},
+};
+
+use super::{
+    builtin::{
+        ClassLayoutPass, CodegenPass, ConstantFoldingPass, ControlFlowPass, DefiniteAssignmentPass,
+        InheritancePass, MemberDefinitionsPass, SymbolCollectorPass, SymbolGlobalsPass,
+        SymbolTablePass, UnusedSymbolsPass, XrefPass,
+    },
+    pass::{Pass, PassContext},
+};
+
+/// What to do with the rest of the pipeline once a pass reports errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Don't run anything after the first pass with errors
+    Stop,
+    /// Keep analysing to find more errors, but skip optimisation and codegen
+    #[default]
+    Continue,
+    /// Run every pass no matter what
+    Force,
+}
+
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+    pub policy: ErrorPolicy,
+    /// Applied to what every pass reports, before the policy decides what runs next
+    pub diagnostics: DiagnosticOptions,
+}
+
+impl PassManager {
+    pub fn new(policy: ErrorPolicy) -> Self {
+        Self {
+            passes: vec![],
+            policy,
+            diagnostics: Default::default(),
+        }
+    }
+
+    /// Everything needed to go from an AST to symbol tables
+    pub fn with_semantic_passes(policy: ErrorPolicy) -> Self {
+        let mut manager = Self::new(policy);
+        manager.register(Box::new(SymbolGlobalsPass));
+        manager.register(Box::new(InheritancePass));
+        manager.register(Box::new(MemberDefinitionsPass));
+        manager.register(Box::new(ClassLayoutPass));
+        manager.register(Box::new(SymbolTablePass));
+        manager.register(Box::new(SymbolCollectorPass));
+        manager.register(Box::new(XrefPass));
+        manager.register(Box::new(ControlFlowPass));
+        manager.register(Box::new(DefiniteAssignmentPass));
+        manager.register(Box::new(UnusedSymbolsPass));
+        manager
+    }
+
+    /// Everything needed to go from an AST to MOON code
+    pub fn with_default_passes(policy: ErrorPolicy) -> Self {
+        let mut manager = Self::with_semantic_passes(policy);
+        manager.register(Box::new(ConstantFoldingPass));
+        manager.register(Box::new(CodegenPass));
+        manager
+    }
+
+    /// Add a pass to the pipeline, where it runs is decided by its stage and what it requires
+    pub fn register(&mut self, pass: Box<dyn Pass>) {
+        self.passes.push(pass);
+    }
+
+    /// Remove a pass by name, returning it if it was registered
+    pub fn unregister(&mut self, name: &str) -> Option<Box<dyn Pass>> {
+        let index = self.passes.iter().position(|p| p.name() == name)?;
+        Some(self.passes.remove(index))
+    }
+
+    pub fn pass_names(&self) -> Vec<&'static str> {
+        self.passes.iter().map(|p| p.name()).collect()
+    }
+
+    /// Order the passes so every pass runs after whatever provides what it requires
+    ///
+    /// Among passes that are ready to run, the earliest stage (then the earliest registered) goes first
+    pub fn order(&self) -> CompilerResult<Vec<&dyn Pass>> {
+        let mut providers: HashMap<&str, usize> = HashMap::new();
+        let mut errors: Vec<CompilerError> = vec![];
+
+        for (i, pass) in self.passes.iter().enumerate() {
+            for output in pass.provides() {
+                if let Some(other) = providers.insert(output, i) {
+                    errors.push(CompilerError::new_with_message(format!(
+                        "Passes '{}' and '{}' both provide '{output}'!",
+                        self.passes[other].name(),
+                        pass.name(),
+                    )));
+                }
+            }
+        }
+
+        let mut dependencies: Vec<Vec<usize>> = vec![];
+
+        for pass in &self.passes {
+            let mut deps = vec![];
+
+            for required in pass.requires() {
+                match providers.get(required) {
+                    Some(provider) => deps.push(*provider),
+                    None => errors.push(CompilerError::new_with_message(format!(
+                        "Pass '{}' requires '{required}', but no registered pass provides it!",
+                        pass.name(),
+                    ))),
+                }
+            }
+
+            dependencies.push(deps);
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut done = vec![false; self.passes.len()];
+        let mut order: Vec<&dyn Pass> = vec![];
+
+        while order.len() < self.passes.len() {
+            let next = (0..self.passes.len())
+                .filter(|&i| !done[i] && dependencies[i].iter().all(|&d| done[d]))
+                .min_by_key(|&i| (self.passes[i].stage(), i));
+
+            match next {
+                Some(i) => {
+                    done[i] = true;
+                    order.push(self.passes[i].as_ref());
+                }
+                None => {
+                    let stuck = (0..self.passes.len())
+                        .filter(|&i| !done[i])
+                        .map(|i| self.passes[i].name())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    return Err(CompilerError::new_with_message(format!(
+                        "Passes depend on each other in a cycle: {stuck}!"
+                    ))
+                    .into());
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Run every pass in order on the tree, collecting outputs and errors in `context`
+    ///
+    /// Only fails if the passes themselves can't be ordered, errors *reported* by passes end up in
+    /// `context.errors`
+    pub fn run(&self, root: &CodeNode, context: &mut PassContext) -> CompilerResult<()> {
+        for pass in self.order()? {
+            let skip = match self.policy {
+                ErrorPolicy::Stop => context.has_errors(),
+                ErrorPolicy::Continue => context.has_errors() && pass.stage().needs_valid_input(),
+                ErrorPolicy::Force => false,
+            };
+
+            if skip {
+                context.skipped.push(pass.name());
+                continue;
+            }
+
+            let res = pass.run(root, context);
+            context.ran.push(pass.name());
+
+            if let Err(e) = res {
+                context.errors.extend(self.diagnostics.filter(e));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::with_default_passes(Default::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexical::lexer::LexerScanner, passes::builtin::MOON_CODE, syntactic::predictive_parser,
+    };
+
+    const ERRORS: &str = include_str!("../../../test/codegen/errors.src");
+
+    fn run(policy: ErrorPolicy) -> PassContext {
+        let mut lexer = LexerScanner::new(ERRORS);
+        let (_, parse_errs, ast_stack) =
+            predictive_parser::parse(&mut lexer).expect("errors.src should parse");
+        let root = ast_stack.first().expect("errors.src should have an AST");
+
+        let mut context = PassContext::new_with_errors(parse_errs);
+        PassManager::with_default_passes(policy)
+            .run(root, &mut context)
+            .expect("Passes should be ordered");
+        context
+    }
+
+    #[test]
+    fn continue_skips_codegen_after_errors() {
+        let context = run(ErrorPolicy::Continue);
+
+        assert!(context.has_errors());
+        assert!(context.skipped.contains(&"codegen"));
+        assert!(!context.has_output(MOON_CODE));
+    }
+
+    #[test]
+    fn force_runs_codegen_on_invalid_trees() {
+        // Codegen reports what it can't generate instead of panicking
+        let context = run(ErrorPolicy::Force);
+
+        assert!(context.skipped.is_empty());
+        assert!(context.ran.contains(&"codegen"));
+        assert!(context.has_output(MOON_CODE));
+        assert!(context.has_errors());
+    }
+}
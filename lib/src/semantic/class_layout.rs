@@ -0,0 +1,305 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue, VarType},
+        symbol_table::{SymbolKind, SymbolTable},
+        tree_node::TreeNode,
+    },
+    compiler_error::CompilerError,
+    lexical::tokens::token_type::Type,
+};
+
+use super::visitor::{Visitor, VisitorResult};
+
+/// Where a single attribute lives inside an object
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberLayout {
+    pub name: String,
+    /// The class that declares the attribute, which isn't the laid out class if it's inherited
+    pub owner: String,
+    /// Relative to the start of the object, going down like everything else on the stack
+    pub offset: isize,
+    pub size: usize,
+    pub var_type: VarType,
+}
+
+/// The memory layout of a whole object, inherited attributes included
+///
+/// Objects start with the attributes of each class in their `isa` list (in order, each laid out
+/// like an object of that class), followed by their own attributes in the order they're declared
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassLayout {
+    pub name: String,
+    pub size: usize,
+    pub members: Vec<MemberLayout>,
+}
+
+impl ClassLayout {
+    /// Find an attribute declared by `owner`, the first one wins if the same class is inherited
+    /// twice
+    pub fn member(&self, owner: &str, name: &str) -> Option<&MemberLayout> {
+        self.members
+            .iter()
+            .find(|m| m.owner == owner && m.name == name)
+    }
+}
+
+pub type ClassLayouts = HashMap<String, ClassLayout>;
+
+/// Computes the layout of every class, then updates the symbol tables to match it
+///
+/// Class sizes end up in the global table and own attributes get their offset in the object.
+/// Inherited attributes stay in the class that declares them, their offset in a derived class is
+/// only in its layout.
+#[derive(Default)]
+pub struct ClassLayoutVisitor {
+    pub layouts: ClassLayouts,
+}
+
+impl ClassLayoutVisitor {
+    pub fn new() -> Self {
+        Self {
+            layouts: Default::default(),
+        }
+    }
+}
+
+impl Visitor for ClassLayoutVisitor {
+    fn visit_program(&mut self, node: &CodeNode, classes_or_funcs: Vec<CodeNode>) -> VisitorResult {
+        let node_ref = node.borrow();
+        let table_ref = node_ref.symbol_table.borrow();
+        let global = match table_ref.as_ref() {
+            Some(global) => global,
+            None => return Ok(()),
+        };
+
+        let class_nodes = classes_or_funcs
+            .into_iter()
+            .filter(|c| matches!(c.borrow().value, NodeValue::Tree(TreeNode::Class())))
+            .filter_map(|c| match c.first_child()?.borrow().value.clone() {
+                NodeValue::Leaf(Type::Id(id)) => Some((id, c.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut builder = LayoutBuilder {
+            global,
+            class_nodes: class_nodes.iter().cloned().collect(),
+            layouts: Default::default(),
+            stack: vec![],
+            failed: HashSet::new(),
+            errors: vec![],
+        };
+
+        for (class_name, _) in &class_nodes {
+            builder.layout(class_name);
+        }
+
+        let LayoutBuilder {
+            layouts, errors, ..
+        } = builder;
+
+        for layout in layouts.values() {
+            apply_layout(global, layout);
+        }
+
+        self.layouts = layouts;
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+struct LayoutBuilder<'a> {
+    global: &'a SymbolTable,
+    class_nodes: HashMap<String, CodeNode>,
+    layouts: ClassLayouts,
+    /// Classes being laid out, with the attribute that led to the next one (if it wasn't inherited)
+    stack: Vec<(String, Option<String>)>,
+    /// Classes that can't be laid out, so errors about them are only reported once
+    failed: HashSet<String>,
+    errors: Vec<CompilerError>,
+}
+
+impl LayoutBuilder<'_> {
+    /// Lay out a class and everything it depends on, returning its size if it could be laid out
+    fn layout(&mut self, class_name: &str) -> Option<usize> {
+        if let Some(layout) = self.layouts.get(class_name) {
+            return Some(layout.size);
+        }
+
+        if self.failed.contains(class_name) {
+            return None;
+        }
+
+        if let Some(start) = self.stack.iter().position(|(c, _)| c == class_name) {
+            self.report_cycle(start);
+            return None;
+        }
+
+        // Unknown classes are reported where they're used
+        let global = self.global;
+        let class_data = global.get(class_name)?.borrow();
+        let class_table = class_data.table.as_ref()?;
+
+        self.stack.push((class_name.to_string(), None));
+
+        let mut members: Vec<MemberLayout> = vec![];
+        let mut size: usize = 0;
+        let mut ok = true;
+
+        for base in &class_table.bases {
+            if !self.global.contains_key(base) {
+                continue;
+            }
+
+            let base_size = match self.layout(base) {
+                Some(base_size) => base_size,
+                None => {
+                    ok = false;
+                    continue;
+                }
+            };
+
+            for member in &self.layouts[base].members {
+                members.push(MemberLayout {
+                    offset: member.offset - size as isize,
+                    ..member.clone()
+                });
+            }
+
+            size += base_size;
+        }
+
+        // symbol_globals gave attributes their offsets in declaration order
+        let mut attributes = class_table
+            .iter()
+            .filter(|(_, data)| data.borrow().kind == SymbolKind::Attribute)
+            .map(|(name, data)| (name.clone(), data.borrow().clone()))
+            .collect::<Vec<_>>();
+        attributes.sort_by_key(|(name, data)| (std::cmp::Reverse(data.offset), name.clone()));
+
+        for (name, data) in attributes {
+            let attribute_size = match &data.var_type {
+                VarType::Class(attribute_class, dims) => {
+                    if !self.global.contains_key(attribute_class) {
+                        self.error(
+                            class_name,
+                            "E0202",
+                            format!("Unknown type '{attribute_class}' for attribute '{name}'!"),
+                        );
+                        ok = false;
+                        continue;
+                    }
+
+                    self.stack.last_mut().unwrap().1 = Some(name.clone());
+                    let attribute_size = self.layout(attribute_class);
+                    self.stack.last_mut().unwrap().1 = None;
+
+                    match attribute_size {
+                        Some(attribute_size) => {
+                            attribute_size * dims.iter().product::<usize>().max(1)
+                        }
+                        None => {
+                            ok = false;
+                            continue;
+                        }
+                    }
+                }
+                _ => data.size,
+            };
+
+            members.push(MemberLayout {
+                name,
+                owner: class_name.to_string(),
+                offset: -(size as isize),
+                size: attribute_size,
+                var_type: data.var_type,
+            });
+
+            size += attribute_size;
+        }
+
+        self.stack.pop();
+
+        if !ok {
+            self.failed.insert(class_name.to_string());
+            return None;
+        }
+
+        self.layouts.insert(
+            class_name.to_string(),
+            ClassLayout {
+                name: class_name.to_string(),
+                size,
+                members,
+            },
+        );
+
+        Some(size)
+    }
+
+    /// Give up on the cycle that starts at `stack[start]` and leads back to it
+    fn report_cycle(&mut self, start: usize) {
+        let cycle = self.stack[start..].to_vec();
+        let class_name = cycle[0].0.clone();
+
+        for (c, _) in &cycle {
+            self.failed.insert(c.clone());
+        }
+
+        // Inheriting from itself is reported by the inheritance check, only report containment
+        if let Some((owner, attribute)) = cycle
+            .iter()
+            .find_map(|(c, attribute)| Some((c, attribute.as_ref()?)))
+        {
+            let message = format!(
+                "Class '{class_name}' contains itself, through attribute '{owner}::{attribute}'!"
+            );
+            self.error(&class_name, "E0114", message);
+        }
+    }
+
+    fn error(&mut self, class_name: &str, code: &'static str, message: String) {
+        let token = self
+            .class_nodes
+            .get(class_name)
+            .and_then(|c| c.first_child())
+            .map(|id| id.borrow().token.clone());
+
+        let error = match token {
+            Some(token) => CompilerError::new(message, token),
+            None => CompilerError::new_with_message(message),
+        };
+        self.errors.push(error.with_code(code));
+    }
+}
+
+/// Make the symbol tables agree with `layout`
+fn apply_layout(global: &SymbolTable, layout: &ClassLayout) {
+    let class_data = match global.get(&layout.name) {
+        Some(class_data) => class_data,
+        None => return,
+    };
+
+    class_data.borrow_mut().size = layout.size;
+
+    // The class table shares its symbols with the one on the class node
+    let class_ref = class_data.borrow();
+    let class_table = match class_ref.table.as_ref() {
+        Some(class_table) => class_table,
+        None => return,
+    };
+
+    for member in layout.members.iter().filter(|m| m.owner == layout.name) {
+        if let Some(data) = class_table.get(&member.name) {
+            let mut data = data.borrow_mut();
+            data.offset = member.offset;
+            data.size = member.size;
+        }
+    }
+}
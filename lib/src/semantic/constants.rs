@@ -0,0 +1,172 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue, VarType},
+        tree_node::TreeNode,
+    },
+    lexical::tokens::token_type::Type,
+};
+
+use super::folder::{new_leaf, new_tree, Folder, FolderResult};
+
+/// Smallest integer MOON can use as an immediate operand (`addi r1, r0, K`), anything that doesn't
+/// fit is left for the program to compute
+pub const MIN_IMMEDIATE: isize = -32768;
+/// Largest integer MOON can use as an immediate operand
+pub const MAX_IMMEDIATE: isize = 32767;
+
+/// The value of an expression made only of literals
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Constant {
+    Integer(isize),
+    Float(f64),
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constant::Integer(value) => write!(f, "{value}"),
+            Constant::Float(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Compute the value of an expression at compile time, if it only uses literals
+///
+/// Integers are computed like MOON would, with 32-bit registers, logical `and`/`or`/`not` and
+/// comparisons giving `0` or `1`. Anything MOON can't compute (dividing by zero, overflowing) or
+/// that mixes integers and floats (which is a type error) isn't a constant.
+pub fn evaluate(node: &CodeNode) -> Option<Constant> {
+    let children: Vec<CodeNode> = node.children().collect();
+
+    match &node.borrow().value {
+        NodeValue::Leaf(Type::IntNum(value)) => integer(*value),
+        NodeValue::Leaf(Type::FloatNum(value)) => Some(Constant::Float(*value)),
+        NodeValue::Tree(TreeNode::Factor() | TreeNode::Expr()) => match children.as_slice() {
+            [expr] => evaluate(expr),
+            [op, factor] => unary(&leaf_type(op)?, evaluate(factor)?),
+            _ => None,
+        },
+        NodeValue::Tree(TreeNode::ArithExpr() | TreeNode::RelExpr()) => match children.as_slice() {
+            [left, op, right] => binary(evaluate(left)?, &leaf_type(op)?, evaluate(right)?),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The value of an integer expression made only of literals
+pub fn const_int(node: &CodeNode) -> Option<isize> {
+    match evaluate(node)? {
+        Constant::Integer(value) => Some(value),
+        Constant::Float(_) => None,
+    }
+}
+
+fn unary(op: &Type, value: Constant) -> Option<Constant> {
+    match (op, value) {
+        (Type::Plus, value) => Some(value),
+        (Type::Minus, Constant::Integer(value)) => integer(-value),
+        (Type::Minus, Constant::Float(value)) => Some(Constant::Float(-value)),
+        (Type::Not, Constant::Integer(value)) => boolean(value == 0),
+        _ => None,
+    }
+}
+
+fn binary(left: Constant, op: &Type, right: Constant) -> Option<Constant> {
+    match (left, right) {
+        (Constant::Integer(left), Constant::Integer(right)) => match op {
+            Type::Plus => integer(left + right),
+            Type::Minus => integer(left - right),
+            Type::Mult => integer(left.checked_mul(right)?),
+            Type::Div => integer(left.checked_div(right)?),
+            Type::And => boolean(left != 0 && right != 0),
+            Type::Or => boolean(left != 0 || right != 0),
+            Type::Eq => boolean(left == right),
+            Type::NotEq => boolean(left != right),
+            Type::Lt => boolean(left < right),
+            Type::LEq => boolean(left <= right),
+            Type::Gt => boolean(left > right),
+            Type::GEq => boolean(left >= right),
+            _ => None,
+        },
+        (Constant::Float(left), Constant::Float(right)) => match op {
+            Type::Plus => Some(Constant::Float(left + right)),
+            Type::Minus => Some(Constant::Float(left - right)),
+            Type::Mult => Some(Constant::Float(left * right)),
+            Type::Div if right != 0.0 => Some(Constant::Float(left / right)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Only values that fit in a MOON register
+fn integer(value: isize) -> Option<Constant> {
+    i32::try_from(value)
+        .ok()
+        .map(|value| Constant::Integer(value as isize))
+}
+
+fn boolean(value: bool) -> Option<Constant> {
+    Some(Constant::Integer(value as isize))
+}
+
+fn leaf_type(node: &CodeNode) -> Option<Type> {
+    match &node.borrow().value {
+        NodeValue::Leaf(leaf) => Some(leaf.clone()),
+        _ => None,
+    }
+}
+
+/// Replaces integer expressions made only of literals with the literal they compute, so codegen
+/// loads the value instead of emitting the arithmetic
+///
+/// Values MOON can't load as an immediate operand are left as they are.
+pub struct ConstantFolder;
+
+impl ConstantFolder {
+    fn fold_constant(&self, node: &CodeNode) -> FolderResult {
+        // Already a literal
+        if node.children().count() == 1
+            && node
+                .first_child()
+                .is_some_and(|c| matches!(c.borrow().value, NodeValue::Leaf(_)))
+        {
+            return Ok(None);
+        }
+
+        let value = match const_int(node) {
+            Some(value) if (MIN_IMMEDIATE..=MAX_IMMEDIATE).contains(&value) => value,
+            _ => return Ok(None),
+        };
+
+        let literal = new_tree(
+            TreeNode::Factor(),
+            node,
+            vec![new_leaf(Type::IntNum(value), node)],
+        );
+        literal
+            .borrow()
+            .var_type
+            .borrow_mut()
+            .replace(VarType::Integer(vec![]));
+
+        Ok(Some(literal))
+    }
+}
+
+impl Folder for ConstantFolder {
+    fn fold_arith_expr(&mut self, node: &CodeNode) -> FolderResult {
+        self.fold_constant(node)
+    }
+
+    fn fold_factor(&mut self, node: &CodeNode) -> FolderResult {
+        self.fold_constant(node)
+    }
+
+    fn fold_rel_expr(&mut self, node: &CodeNode) -> FolderResult {
+        self.fold_constant(node)
+    }
+}
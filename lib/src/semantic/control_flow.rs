@@ -0,0 +1,146 @@
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue},
+        tree_node::TreeNode,
+    },
+    compiler_error::{CompilerError, WarningKind},
+    lexical::tokens::token_type::Type,
+};
+
+use super::{visitor::VisitorResult, visitor_utils::get_current_function_name};
+
+/// Checks how control flows out of function bodies
+///
+/// Functions with a return type must return on every path, `void` functions (and constructors)
+/// can't return a value, and statements after a `return` (or after an `if` returning in both
+/// branches) are never run.
+#[derive(Default)]
+pub struct ControlFlowChecker {
+    errors: Vec<CompilerError>,
+}
+
+impl ControlFlowChecker {
+    pub fn new() -> Self {
+        Self { errors: vec![] }
+    }
+
+    pub fn check(&mut self, root: &CodeNode) -> VisitorResult {
+        for function in root.children() {
+            if !matches!(
+                function.borrow().value,
+                NodeValue::Tree(TreeNode::Function())
+            ) {
+                continue;
+            }
+
+            let (head, body) = match (function.first_child(), function.last_child()) {
+                (Some(head), Some(body)) => (head, body),
+                _ => continue,
+            };
+
+            self.check_function(&head, &body);
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn check_function(&mut self, head: &CodeNode, body: &CodeNode) {
+        let name = get_current_function_name(head).unwrap_or_default();
+
+        // The return type comes after the parameters, constructors don't have one
+        let returns_value = head
+            .children()
+            .nth(2)
+            .is_some_and(|r| !matches!(r.borrow().value, NodeValue::Leaf(Type::Void)));
+
+        if !returns_value {
+            for node in body.descendants() {
+                if matches!(node.borrow().value, NodeValue::Tree(TreeNode::Return())) {
+                    self.errors.push(
+                        CompilerError::new_with_node(
+                            format!(
+                            "Function '{name}' doesn't return anything, it can't return a value!"
+                        ),
+                            &node.borrow(),
+                        )
+                        .with_code("E0402"),
+                    );
+                }
+            }
+        }
+
+        let falls_through = self.check_block(body);
+
+        if falls_through && returns_value {
+            let id = head.first_child().unwrap_or_else(|| head.clone());
+            self.errors.push(
+                CompilerError::new_with_node(
+                    format!("Function '{name}' can reach its end without returning a value!"),
+                    &id.borrow(),
+                )
+                .with_code("E0401"),
+            );
+        }
+    }
+
+    /// Whether running the block can reach its end
+    fn check_block(&mut self, block: &CodeNode) -> bool {
+        let mut reachable = true;
+        let mut reported = false;
+
+        for statement in block.children() {
+            if !reachable && !reported {
+                self.errors.push(
+                    CompilerError::new_with_node(
+                        "Unreachable statement, every path before it returns".to_string(),
+                        &statement.borrow(),
+                    )
+                    .with_code("W0402")
+                    .warning(WarningKind::Unreachable),
+                );
+                // Once per block is plenty
+                reported = true;
+            }
+
+            reachable &= self.check_statement(&statement);
+        }
+
+        reachable
+    }
+
+    /// Whether running the statement can get to whatever comes after it
+    fn check_statement(&mut self, statement: &CodeNode) -> bool {
+        let tree_node = match &statement.borrow().value {
+            NodeValue::Tree(tree_node) => tree_node.clone(),
+            _ => return true,
+        };
+
+        match tree_node {
+            TreeNode::Return() => false,
+            TreeNode::If() => {
+                let mut blocks = statement.children().skip(1);
+                match (blocks.next(), blocks.next()) {
+                    (Some(if_block), Some(else_block)) => {
+                        // Check both blocks, to report unreachable statements in each
+                        let if_reachable = self.check_block(&if_block);
+                        let else_reachable = self.check_block(&else_block);
+                        if_reachable || else_reachable
+                    }
+                    _ => true,
+                }
+            }
+            TreeNode::While() => {
+                // The condition might be false from the start, so what's after is always reachable
+                if let Some(block) = statement.last_child() {
+                    self.check_block(&block);
+                }
+                true
+            }
+            _ => true,
+        }
+    }
+}
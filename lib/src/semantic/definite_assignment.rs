@@ -0,0 +1,186 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue},
+        tree_node::TreeNode,
+    },
+    compiler_error::{CompilerError, WarningKind},
+    lexical::tokens::token_type::Type,
+};
+
+use super::visitor::VisitorResult;
+
+/// Locals that are definitely assigned at some point of a function body, `None` once the point
+/// can't be reached (after a `return`), where anything counts as assigned
+type Assigned = Option<HashSet<String>>;
+
+/// Warns when a `localvar` may be read before it's definitely assigned
+///
+/// Follows each function body in order: both branches of an `if` have to assign a variable for it to
+/// be assigned after it, a `while` body might not run at all, and nothing after a `return` is
+/// reachable. Only scalar (`integer` and `float`) locals are tracked, arrays and objects are written
+/// one element or attribute at a time.
+#[derive(Default)]
+pub struct DefiniteAssignmentChecker {
+    /// Tracked locals of the current function, with where they're declared
+    declarations: HashMap<String, CodeNode>,
+    errors: Vec<CompilerError>,
+}
+
+impl DefiniteAssignmentChecker {
+    pub fn new() -> Self {
+        Self {
+            declarations: HashMap::new(),
+            errors: vec![],
+        }
+    }
+
+    pub fn check(&mut self, root: &CodeNode) -> VisitorResult {
+        for function in root.children() {
+            if !matches!(
+                function.borrow().value,
+                NodeValue::Tree(TreeNode::Function())
+            ) {
+                continue;
+            }
+
+            if let Some(body) = function.last_child() {
+                self.declarations.clear();
+                self.check_block(&body, Some(HashSet::new()));
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn check_block(&mut self, block: &CodeNode, mut assigned: Assigned) -> Assigned {
+        for statement in block.children() {
+            assigned = self.check_statement(&statement, assigned);
+        }
+
+        assigned
+    }
+
+    fn check_statement(&mut self, statement: &CodeNode, mut assigned: Assigned) -> Assigned {
+        let tree_node = match &statement.borrow().value {
+            NodeValue::Tree(tree_node) => tree_node.clone(),
+            _ => return assigned,
+        };
+        let children = statement.children().collect::<Vec<_>>();
+
+        match (tree_node, children.as_slice()) {
+            (TreeNode::LocalVarDecl(), [id, var_type, indices, ..]) => {
+                let is_scalar = matches!(
+                    var_type.borrow().value,
+                    NodeValue::Leaf(Type::Integer) | NodeValue::Leaf(Type::Float)
+                ) && !indices.has_children();
+
+                if let (true, NodeValue::Leaf(Type::Id(name))) = (is_scalar, &id.borrow().value) {
+                    self.declarations.insert(name.clone(), id.clone());
+                    if let Some(assigned) = assigned.as_mut() {
+                        assigned.remove(name);
+                    }
+                }
+
+                assigned
+            }
+            (TreeNode::Assignment(), [target, expr]) => {
+                self.check_uses(expr, &mut assigned);
+                self.check_target(target, assigned)
+            }
+            (TreeNode::Read(), [target]) => self.check_target(target, assigned),
+            (TreeNode::If(), [condition, if_block, else_block]) => {
+                self.check_uses(condition, &mut assigned);
+                let if_assigned = self.check_block(if_block, assigned.clone());
+                let else_assigned = self.check_block(else_block, assigned);
+
+                match (if_assigned, else_assigned) {
+                    (Some(a), Some(b)) => Some(a.intersection(&b).cloned().collect()),
+                    (a, None) => a,
+                    (None, b) => b,
+                }
+            }
+            (TreeNode::While(), [condition, block]) => {
+                self.check_uses(condition, &mut assigned);
+                self.check_block(block, assigned.clone());
+                assigned
+            }
+            (TreeNode::Return(), _) => {
+                self.check_uses(statement, &mut assigned);
+                None
+            }
+            _ => {
+                self.check_uses(statement, &mut assigned);
+                assigned
+            }
+        }
+    }
+
+    /// Assign the target of an assignment or `read`, anything in it other than the variable itself
+    /// (indices, the object of a member) is read
+    fn check_target(&mut self, target: &CodeNode, mut assigned: Assigned) -> Assigned {
+        match variable_name(target) {
+            Some(name) => {
+                if let Some(assigned) = assigned.as_mut() {
+                    assigned.insert(name);
+                }
+            }
+            None => self.check_uses(target, &mut assigned),
+        }
+
+        assigned
+    }
+
+    /// Report every tracked local read in `node` that isn't assigned yet
+    fn check_uses(&mut self, node: &CodeNode, assigned: &mut Assigned) {
+        let assigned_set = match assigned.as_mut() {
+            Some(assigned) => assigned,
+            None => return,
+        };
+
+        if let Some(name) = variable_name(node) {
+            if let Some(declared) = self.declarations.get(&name) {
+                if !assigned_set.contains(&name) {
+                    self.errors.push(
+                        CompilerError::new_with_node(
+                            format!("Variable '{name}' may be used before it's assigned"),
+                            &node.borrow(),
+                        )
+                        .with_code("W0401")
+                        .warning(WarningKind::Uninitialized)
+                        .with_note(format!("'{name}' is declared here"), &declared.borrow()),
+                    );
+
+                    // Only report the first read
+                    assigned_set.insert(name);
+                }
+            }
+            return;
+        }
+
+        for child in node.children() {
+            self.check_uses(&child, assigned);
+        }
+    }
+}
+
+/// The name of a `Variable` that is just an id, no indices
+fn variable_name(node: &CodeNode) -> Option<String> {
+    if !matches!(node.borrow().value, NodeValue::Tree(TreeNode::Variable())) {
+        return None;
+    }
+
+    let mut children = node.children();
+    match (children.next(), children.next()) {
+        (Some(id), Some(indices)) if !indices.has_children() => match &id.borrow().value {
+            NodeValue::Leaf(Type::Id(name)) => Some(name.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
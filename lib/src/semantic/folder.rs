@@ -0,0 +1,286 @@
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue, StructNode},
+        tree_node::TreeNode,
+    },
+    compiler_error::{CompilerError, CompilerResult},
+    lexical::tokens::token_type::Type,
+};
+
+/// `Ok(None)` keeps the node as is, `Ok(Some(node))` replaces it in the tree
+pub type FolderResult = CompilerResult<Option<CodeNode>>;
+
+/// Like `Visitor`, but every method can return a replacement for the node it's given
+///
+/// Children are folded before their parents, so by the time `fold_x` runs the node's children
+/// are already the rewritten ones. Replacements are spliced into the tree in place of the old
+/// node, which is detached.
+pub trait Folder {
+    /// Fold the tree rooted at `node`, returning the new root (which is `node` unless it was replaced)
+    fn fold(&mut self, node: &CodeNode) -> CompilerResult<CodeNode> {
+        let mut errors: Vec<CompilerError> = Vec::new();
+
+        // Collect first, since folding a child can swap it out from under the iterator
+        let children: Vec<CodeNode> = node.children().collect();
+        for child in children {
+            if let Err(e) = self.fold(&child) {
+                errors.extend(e);
+            };
+        }
+
+        let value = node.borrow().value.clone();
+        let replacement = match &value {
+            NodeValue::Leaf(l) => self.fold_leaf(node, l),
+            NodeValue::Tree(t) => self.fold_tree(node, t),
+            NodeValue::Marker => Err(vec![CompilerError::new(
+                "Unexpected marker node!".to_string(),
+                node.borrow().token.clone(),
+            )]),
+        };
+
+        let new_node = match replacement {
+            Ok(Some(new_node)) => {
+                replace_node(node, &new_node);
+                new_node
+            }
+            Ok(None) => node.clone(),
+            Err(e) => {
+                errors.extend(e);
+                node.clone()
+            }
+        };
+
+        if errors.is_empty() {
+            Ok(new_node)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn fold_leaf(&mut self, _node: &CodeNode, _leaf: &Type) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_tree(&mut self, node: &CodeNode, tree_node: &TreeNode) -> FolderResult {
+        match tree_node {
+            TreeNode::ArgumentList() => self.fold_argument_list(node),
+            TreeNode::ArithExpr() => self.fold_arith_expr(node),
+            TreeNode::Assignment() => self.fold_assignment(node),
+            TreeNode::Attribute() => self.fold_attribute(node),
+            TreeNode::Class() => self.fold_class(node),
+            TreeNode::ClassMembers() => self.fold_class_members(node),
+            TreeNode::ConstructorFunc() => self.fold_constructor_func(node),
+            TreeNode::ElseBlock() => self.fold_else_block(node),
+            TreeNode::Expr() => self.fold_expr(node),
+            TreeNode::Factor() => self.fold_factor(node),
+            TreeNode::Function() => self.fold_function(node),
+            TreeNode::FunctionBody() => self.fold_function_body(node),
+            TreeNode::FunctionCall() => self.fold_function_call(node),
+            TreeNode::FunctionHead() => self.fold_function_head(node),
+            TreeNode::If() => self.fold_if(node),
+            TreeNode::IfBlock() => self.fold_if_block(node),
+            TreeNode::IndexedVar() => self.fold_indexed_var(node),
+            TreeNode::IndiceList() => self.fold_indice_list(node),
+            TreeNode::InheritsList() => self.fold_inherits_list(node),
+            TreeNode::LocalVarDecl() => self.fold_local_var_decl(node),
+            TreeNode::MemberFunc() => self.fold_member_func(node),
+            TreeNode::NestedVar() => self.fold_nested_var(node),
+            TreeNode::Parameter() => self.fold_parameter(node),
+            TreeNode::ParameterList() => self.fold_parameter_list(node),
+            TreeNode::Program() => self.fold_program(node),
+            TreeNode::Read() => self.fold_read(node),
+            TreeNode::RelExpr() => self.fold_rel_expr(node),
+            TreeNode::Return() => self.fold_return(node),
+            TreeNode::Scope() => self.fold_scope(node),
+            TreeNode::Variable() => self.fold_variable(node),
+            TreeNode::While() => self.fold_while(node),
+            TreeNode::WhileBlock() => self.fold_while_block(node),
+            TreeNode::Write() => self.fold_write(node),
+        }
+    }
+
+    fn fold_argument_list(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_arith_expr(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_assignment(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_attribute(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_class(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_class_members(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_constructor_func(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_else_block(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_expr(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_factor(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_function(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_function_body(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_function_call(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_function_head(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_if(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_if_block(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_indexed_var(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_indice_list(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_inherits_list(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_local_var_decl(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_member_func(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_nested_var(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_parameter(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_parameter_list(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_program(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_read(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_rel_expr(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_return(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_scope(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_variable(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_while(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_while_block(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+
+    fn fold_write(&mut self, _node: &CodeNode) -> FolderResult {
+        Ok(None)
+    }
+}
+
+/// Put `new` where `old` is in the tree, detaching `old`
+pub fn replace_node(old: &CodeNode, new: &CodeNode) {
+    // Nodes compare by pointer
+    if old == new {
+        return;
+    }
+
+    new.detach();
+
+    if old.parent().is_some() {
+        old.insert_after(new.clone());
+    }
+
+    old.detach();
+}
+
+/// Create a new subtree out of `children`, taking its token and span from `origin`
+///
+/// The children are moved out of wherever they currently are in the tree
+pub fn new_tree(tree_node: TreeNode, origin: &CodeNode, children: Vec<CodeNode>) -> CodeNode {
+    let subtree = new_with_origin(NodeValue::Tree(tree_node), origin);
+
+    for child in children {
+        child.detach();
+        subtree.append(child);
+    }
+
+    subtree
+}
+
+/// Create a new leaf, taking its token and span from `origin`
+pub fn new_leaf(leaf: Type, origin: &CodeNode) -> CodeNode {
+    new_with_origin(NodeValue::Leaf(leaf), origin)
+}
+
+/// Copy a whole subtree, giving every node a fresh id and no semantic info (tables, types, ...)
+pub fn copy_subtree(node: &CodeNode) -> CodeNode {
+    let copy = new_with_origin(node.borrow().value.clone(), node);
+
+    for child in node.children() {
+        copy.append(copy_subtree(&child));
+    }
+
+    copy
+}
+
+fn new_with_origin(value: NodeValue, origin: &CodeNode) -> CodeNode {
+    let origin_ref = origin.borrow();
+    let node = StructNode::new_node(value, origin_ref.token.clone());
+    node.borrow_mut().span = origin_ref.span.clone();
+    node
+}
@@ -0,0 +1,322 @@
+use std::collections::HashSet;
+
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue},
+        tree_node::TreeNode,
+    },
+    compiler_error::{CompilerError, WarningKind},
+    lexical::tokens::{token::Token, token_type::Type},
+};
+
+use super::{
+    visitor::{Visitor, VisitorResult},
+    visitor_utils::Signature,
+};
+
+/// What a class declares, as far as inheritance is concerned
+struct ClassInfo {
+    name: String,
+    node: CodeNode,
+    /// Base classes and the leaves naming them, in `isa` order
+    bases: Vec<(String, CodeNode)>,
+    attributes: Vec<(String, CodeNode)>,
+    /// Member functions by name, along with their full signature
+    functions: Vec<(String, Signature, CodeNode)>,
+}
+
+/// Checks the `isa` lists of every class
+///
+/// Base classes must exist and classes can't inherit from themselves, even indirectly. Classes
+/// redeclaring inherited attributes, or inherited member functions with a different signature,
+/// are only warned about.
+#[derive(Default)]
+pub struct InheritanceVisitor {
+    classes: Vec<ClassInfo>,
+}
+
+impl InheritanceVisitor {
+    pub fn new() -> Self {
+        Self { classes: vec![] }
+    }
+
+    fn get_class(&self, name: &str) -> Option<&ClassInfo> {
+        // Duplicate classes are reported by the collector, the first one is the one that's kept
+        self.classes.iter().find(|c| c.name == name)
+    }
+
+    /// Every class `class_name` inherits from, closest first (depth-first in `isa` order)
+    fn ancestors(&self, class_name: &str) -> Vec<&ClassInfo> {
+        let mut ancestors: Vec<&ClassInfo> = vec![];
+        let mut to_search: Vec<&str> = vec![class_name];
+
+        while let Some(name) = to_search.pop() {
+            let class = match self.get_class(name) {
+                Some(class) => class,
+                None => continue,
+            };
+
+            if name != class_name {
+                if ancestors.iter().any(|a| a.name == name) {
+                    continue;
+                }
+                ancestors.push(class);
+            }
+
+            to_search.extend(class.bases.iter().rev().map(|(b, _)| b.as_str()));
+        }
+
+        ancestors
+    }
+
+    /// Find the inheritance cycles, reporting each one once
+    fn check_cycles(&self, errors: &mut Vec<CompilerError>) -> HashSet<String> {
+        let mut done: HashSet<String> = HashSet::new();
+        let mut cyclic: HashSet<String> = HashSet::new();
+
+        for class in &self.classes {
+            let mut path: Vec<&str> = vec![];
+            self.find_cycles(&class.name, &mut path, &mut done, &mut cyclic, errors);
+        }
+
+        cyclic
+    }
+
+    fn find_cycles<'a>(
+        &'a self,
+        class_name: &'a str,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<String>,
+        cyclic: &mut HashSet<String>,
+        errors: &mut Vec<CompilerError>,
+    ) {
+        if let Some(start) = path.iter().position(|c| *c == class_name) {
+            let chain = path[start..]
+                .iter()
+                .chain([&class_name])
+                .copied()
+                .collect::<Vec<_>>()
+                .join(" isa ");
+
+            cyclic.extend(path[start..].iter().map(|c| c.to_string()));
+
+            let class = self.get_class(class_name).unwrap();
+            errors.push(
+                CompilerError::new(
+                    format!("Circular inheritance detected! {chain}"),
+                    id_token(&class.node),
+                )
+                .with_code("E0107"),
+            );
+            return;
+        }
+
+        if done.contains(class_name) {
+            return;
+        }
+
+        let class = match self.get_class(class_name) {
+            Some(class) => class,
+            None => return,
+        };
+
+        path.push(class_name);
+        for (base, _) in &class.bases {
+            self.find_cycles(base, path, done, cyclic, errors);
+        }
+        path.pop();
+
+        done.insert(class_name.to_string());
+    }
+}
+
+impl Visitor for InheritanceVisitor {
+    fn visit_program(
+        &mut self,
+        _node: &CodeNode,
+        _classes_or_funcs: Vec<CodeNode>,
+    ) -> VisitorResult {
+        let mut errors: Vec<CompilerError> = vec![];
+
+        for class in &self.classes {
+            for (base, base_node) in &class.bases {
+                if self.get_class(base).is_none() {
+                    errors.push(
+                        CompilerError::new(
+                            format!(
+                                "Class '{}' inherits from unknown class '{base}'!",
+                                class.name
+                            ),
+                            base_node.borrow().token.clone(),
+                        )
+                        .with_code("E0106"),
+                    );
+                }
+            }
+        }
+
+        let cyclic = self.check_cycles(&mut errors);
+        let mut checked: HashSet<&str> = HashSet::new();
+
+        for class in &self.classes {
+            // Members of classes in a cycle inherit from themselves, there's nothing useful to say
+            if cyclic.contains(&class.name) || !checked.insert(&class.name) {
+                continue;
+            }
+
+            let ancestors = self.ancestors(&class.name);
+
+            for (attribute, attribute_node) in &class.attributes {
+                let shadowed = ancestors.iter().find_map(|a| {
+                    a.attributes
+                        .iter()
+                        .find(|(name, _)| name == attribute)
+                        .map(|(_, base_node)| (a, base_node))
+                });
+
+                if let Some((base, base_node)) = shadowed {
+                    errors.push(
+                        CompilerError::new(
+                            format!(
+                                "Attribute '{}::{attribute}' shadows inherited attribute '{}::{attribute}'",
+                                class.name, base.name,
+                            ),
+                            attribute_node.borrow().token.clone(),
+                        )
+                        .with_code("W0101")
+                        .warning(WarningKind::Shadowing)
+                        .with_note(
+                            format!("'{}::{attribute}' is declared here", base.name),
+                            &base_node.borrow(),
+                        ),
+                    );
+                }
+            }
+
+            for (function, signature, function_node) in &class.functions {
+                // Overloads have the same name, it only overrides the one with the same parameters
+                let inherited = ancestors
+                    .iter()
+                    .flat_map(|a| {
+                        a.functions
+                            .iter()
+                            .filter(|(name, _, _)| name == function)
+                            .map(move |(_, base_signature, base_node)| {
+                                (a, base_signature, base_node)
+                            })
+                    })
+                    .collect::<Vec<_>>();
+                let overridden = inherited
+                    .iter()
+                    .find(|(_, base_signature, _)| base_signature.params == signature.params)
+                    .or(inherited.first());
+
+                if let Some((base, base_signature, base_node)) = overridden {
+                    if *base_signature != signature {
+                        errors.push(
+                            CompilerError::new(
+                                format!(
+                                    "Member function '{}::{signature}' overrides '{}::{base_signature}' with a different signature",
+                                    class.name, base.name,
+                                ),
+                                function_node.borrow().token.clone(),
+                            )
+                            .with_code("W0102")
+                            .warning(WarningKind::Override)
+                            .with_note(
+                                format!("'{}::{base_signature}' is declared here", base.name),
+                                &base_node.borrow(),
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn visit_class(
+        &mut self,
+        node: &CodeNode,
+        id: Type,
+        inherits: CodeNode,
+        members: CodeNode,
+    ) -> VisitorResult {
+        let name = match id {
+            Type::Id(id) => id,
+            _ => {
+                return Err(CompilerError::new(
+                    format!("Expected identifier at '{}'!", node.borrow().value),
+                    node.borrow().token.clone(),
+                )
+                .into())
+            }
+        };
+
+        let bases = inherits
+            .children()
+            .filter_map(|base| Some((leaf_id(&base)?, base)))
+            .collect();
+
+        let mut attributes = vec![];
+        let mut functions = vec![];
+
+        for member in members.children() {
+            let member_value = member.borrow().value.clone();
+            let id_node = match member.children().nth(1) {
+                Some(id_node) => id_node,
+                None => continue,
+            };
+            let member_name = match leaf_id(&id_node) {
+                Some(member_name) => member_name,
+                None => continue,
+            };
+
+            match member_value {
+                NodeValue::Tree(TreeNode::Attribute()) => attributes.push((member_name, id_node)),
+                NodeValue::Tree(TreeNode::MemberFunc()) => {
+                    let mut children = member.children().skip(2);
+                    let signature = match children.next() {
+                        Some(param_list) => Signature::from_nodes(
+                            &member_name,
+                            &param_list,
+                            children.next().as_ref(),
+                        ),
+                        None => continue,
+                    };
+                    functions.push((member_name, signature, id_node));
+                }
+                _ => (),
+            }
+        }
+
+        self.classes.push(ClassInfo {
+            name,
+            node: node.clone(),
+            bases,
+            attributes,
+            functions,
+        });
+
+        Ok(())
+    }
+}
+
+fn leaf_id(node: &CodeNode) -> Option<String> {
+    match &node.borrow().value {
+        NodeValue::Leaf(Type::Id(id)) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+fn id_token(class_node: &CodeNode) -> Token {
+    match class_node.first_child() {
+        Some(id) => id.borrow().token.clone(),
+        None => class_node.borrow().token.clone(),
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue},
+        tree_node::TreeNode,
+    },
+    compiler_error::CompilerError,
+    lexical::tokens::token_type::Type,
+};
+
+use super::{
+    visitor::{Visitor, VisitorResult},
+    visitor_utils::Signature,
+};
+
+/// A member function, either declared in a class or defined as `function A::f(...)`
+struct Member {
+    class_name: String,
+    signature: Signature,
+    /// The leaf naming the function, where errors point to
+    id_node: CodeNode,
+}
+
+/// Pairs every `function A::f(...)` definition with the `MemberFunc` (or constructor) declared in
+/// class `A`, by full signature and return type
+///
+/// Reports definitions without a declaration, declarations without a definition, and definitions
+/// that only match a declaration by name (with both locations).
+#[derive(Default)]
+pub struct MemberDefinitionVisitor {
+    classes: Vec<String>,
+    declarations: Vec<Member>,
+    definitions: Vec<Member>,
+}
+
+impl MemberDefinitionVisitor {
+    pub fn new() -> Self {
+        Self {
+            classes: vec![],
+            declarations: vec![],
+            definitions: vec![],
+        }
+    }
+}
+
+impl Visitor for MemberDefinitionVisitor {
+    fn visit_program(
+        &mut self,
+        _node: &CodeNode,
+        _classes_or_funcs: Vec<CodeNode>,
+    ) -> VisitorResult {
+        let mut errors: Vec<CompilerError> = vec![];
+        let mut defined = vec![false; self.declarations.len()];
+
+        for definition in &self.definitions {
+            let class_name = &definition.class_name;
+            let signature = &definition.signature;
+            let location = definition.id_node.borrow().token.clone();
+
+            if !self.classes.contains(class_name) {
+                errors.push(
+                    CompilerError::new(
+                        format!("Member function '{class_name}::{signature}' is defined for unknown class '{class_name}'!"),
+                        location,
+                    )
+                    .with_code("E0108"),
+                );
+                continue;
+            }
+
+            let candidates = self
+                .declarations
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| &d.class_name == class_name && d.signature.name == signature.name)
+                .collect::<Vec<_>>();
+
+            if let Some((i, _)) = candidates.iter().find(|(_, d)| &d.signature == signature) {
+                defined[*i] = true;
+                continue;
+            }
+
+            // Only pair it with a declaration nothing else defines, so each mismatch is reported once
+            let (i, declaration) = match candidates.iter().find(|(i, _)| !defined[*i]) {
+                Some(candidate) => *candidate,
+                None => {
+                    errors.push(
+                        CompilerError::new(
+                            format!("Member function '{class_name}::{signature}' is defined but never declared in class '{class_name}'!"),
+                            location,
+                        )
+                        .with_code("E0109"),
+                    );
+                    continue;
+                }
+            };
+
+            defined[i] = true;
+
+            let declared = &declaration.signature;
+            let declared_at = declaration.id_node.borrow().token.location.clone();
+
+            let (message, code) = if declared.params == signature.params {
+                (format!(
+                    "Return type of '{class_name}::{signature}' doesn't match its declaration at {declared_at}, expected '{}'!",
+                    declared.return_type.as_deref().unwrap_or("nothing"),
+                ), "E0111")
+            } else {
+                (format!(
+                    "Parameters of '{class_name}::{signature}' don't match its declaration at {declared_at}, expected '({})'!",
+                    declared.params.join(", "),
+                ), "E0112")
+            };
+
+            errors.push(CompilerError::new(message, location).with_code(code));
+        }
+
+        for (declaration, defined) in self.declarations.iter().zip(defined) {
+            if !defined {
+                errors.push(
+                    CompilerError::new(
+                        format!(
+                            "Member function '{}::{}' is declared but never defined!",
+                            declaration.class_name, declaration.signature
+                        ),
+                        declaration.id_node.borrow().token.clone(),
+                    )
+                    .with_code("E0110"),
+                );
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn visit_class(
+        &mut self,
+        _node: &CodeNode,
+        id: Type,
+        _inherits: CodeNode,
+        members: CodeNode,
+    ) -> VisitorResult {
+        let class_name = match id {
+            Type::Id(id) => id,
+            _ => return Ok(()),
+        };
+
+        // Duplicate classes are reported by the collector, only the first one counts
+        if self.classes.contains(&class_name) {
+            return Ok(());
+        }
+
+        for member in members.children() {
+            let is_function = matches!(
+                member.borrow().value,
+                NodeValue::Tree(TreeNode::MemberFunc())
+                    | NodeValue::Tree(TreeNode::ConstructorFunc())
+            );
+
+            if !is_function {
+                continue;
+            }
+
+            let mut children = member.children().skip(1);
+            let (id_node, param_list) = match (children.next(), children.next()) {
+                (Some(id_node), Some(param_list)) => (id_node, param_list),
+                _ => continue,
+            };
+
+            let name = match function_name(&id_node) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            self.declarations.push(Member {
+                class_name: class_name.clone(),
+                signature: Signature::from_nodes(&name, &param_list, children.next().as_ref()),
+                id_node,
+            });
+        }
+
+        self.classes.push(class_name);
+
+        Ok(())
+    }
+
+    fn visit_function_head(
+        &mut self,
+        _node: &CodeNode,
+        id: CodeNode,
+        param_list: CodeNode,
+        _return_type: Option<Type>,
+    ) -> VisitorResult {
+        if !matches!(id.borrow().value, NodeValue::Tree(TreeNode::Scope())) {
+            return Ok(());
+        }
+
+        let mut scope = id.children();
+        let (class_name, id_node) = match (scope.next(), scope.next()) {
+            (Some(class_id), Some(id_node)) => match &class_id.borrow().value {
+                NodeValue::Leaf(Type::Id(class_name)) => (class_name.clone(), id_node),
+                _ => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+
+        let name = match function_name(&id_node) {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+
+        // The return type leaf comes right after the parameters, constructors don't have one
+        let return_type = param_list.next_sibling();
+
+        self.definitions.push(Member {
+            class_name,
+            signature: Signature::from_nodes(&name, &param_list, return_type.as_ref()),
+            id_node,
+        });
+
+        Ok(())
+    }
+}
+
+/// Member functions are named by an id, or the `constructor` keyword
+fn function_name(id_node: &CodeNode) -> Option<String> {
+    match &id_node.borrow().value {
+        NodeValue::Leaf(Type::Id(name)) => Some(name.clone()),
+        NodeValue::Leaf(Type::Constructor) => Some(String::from("constructor")),
+        _ => None,
+    }
+}
@@ -1,5 +1,15 @@
+pub mod class_layout;
+pub mod constants;
+pub mod control_flow;
+pub mod definite_assignment;
+pub mod folder;
+pub mod inheritance;
+pub mod member_definitions;
+pub mod overloads;
 pub mod symbol_collector;
 pub mod symbol_globals;
 pub mod symbol_visitor;
+pub mod unused_symbols;
 pub mod visitor;
 pub mod visitor_utils;
+pub mod xref;
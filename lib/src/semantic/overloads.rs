@@ -0,0 +1,195 @@
+use crate::{
+    ast::{
+        nodes::{StructNode, VarType},
+        symbol_table::{SymbolKind, SymbolTable},
+    },
+    compiler_error::CompilerError,
+};
+
+/// A function that could be the target of a call, by its global table key
+struct Candidate {
+    key: String,
+    params: Vec<VarType>,
+}
+
+/// Why a call couldn't be resolved
+pub struct OverloadError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl OverloadError {
+    fn new(code: &'static str, message: String) -> Self {
+        Self { code, message }
+    }
+
+    /// Report the error at the call
+    pub fn into_error(self, call: &StructNode) -> CompilerError {
+        CompilerError::new_with_node(self.message, call).with_code(self.code)
+    }
+}
+
+/// Pick the function `name(args)` calls among every function called `name`
+///
+/// `name` is `Class::f` for member functions. Arguments without a type or with the `Error` type
+/// (because of an error that's already been reported) match any parameter. Exact matches win over arguments that only match
+/// an unsized (`integer[]`) parameter, and a tie between the best candidates is ambiguous.
+///
+/// Returns the global table key of the chosen function, or why there isn't one.
+pub fn resolve_overload(
+    global: &SymbolTable,
+    name: &str,
+    args: &[Option<VarType>],
+) -> Result<String, OverloadError> {
+    resolve_among(get_candidates(global, name), name, args)
+}
+
+/// Pick the constructor of `class_name` that `args` are passed to, among the ones its class
+/// declares, the same way as `resolve_overload`
+///
+/// Constructors don't have to be defined to be picked, a missing definition is reported on its
+/// own. Returns the global table key the definition has (or would have).
+pub fn resolve_constructor(
+    class_table: &SymbolTable,
+    class_name: &str,
+    args: &[Option<VarType>],
+) -> Result<String, OverloadError> {
+    let mut candidates = class_table
+        .iter()
+        .filter(|(key, data)| {
+            data.borrow().kind == SymbolKind::Function && key.starts_with("constructor(")
+        })
+        .map(|(key, data)| Candidate {
+            key: format!("{class_name}::{key}"),
+            params: data.borrow().params.clone(),
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| a.key.cmp(&b.key));
+
+    resolve_among(candidates, &format!("{class_name}::constructor"), args)
+}
+
+fn resolve_among(
+    candidates: Vec<Candidate>,
+    name: &str,
+    args: &[Option<VarType>],
+) -> Result<String, OverloadError> {
+    let call = format!(
+        "{name}({})",
+        args.iter()
+            .map(|a| match a {
+                Some(VarType::Error) | None => String::from("?"),
+                Some(a) => a.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    if candidates.is_empty() {
+        return Err(OverloadError::new(
+            "E0203",
+            format!("Function '{call}' not found!"),
+        ));
+    }
+
+    let ranked = candidates
+        .iter()
+        .filter_map(|c| Some((call_cost(&c.params, args)?, c)))
+        .collect::<Vec<_>>();
+
+    let best_cost = match ranked.iter().map(|(cost, _)| *cost).min() {
+        Some(best_cost) => best_cost,
+        None => {
+            return Err(OverloadError::new(
+                "E0204",
+                format!(
+                    "No matching overload for '{call}', candidates are: {}!",
+                    list_keys(candidates.iter())
+                ),
+            ))
+        }
+    };
+
+    let best = ranked
+        .into_iter()
+        .filter(|(cost, _)| *cost == best_cost)
+        .map(|(_, c)| c)
+        .collect::<Vec<_>>();
+
+    match best.as_slice() {
+        [target] => Ok(target.key.clone()),
+        _ => Err(OverloadError::new(
+            "E0205",
+            format!(
+                "Call to '{call}' is ambiguous, it could be any of: {}!",
+                list_keys(best.into_iter())
+            ),
+        )),
+    }
+}
+
+fn get_candidates(global: &SymbolTable, name: &str) -> Vec<Candidate> {
+    let mut candidates = global
+        .iter()
+        .filter(|(key, data)| {
+            data.borrow().var_type == VarType::Function
+                && key.split_once('(').map(|(n, _)| n) == Some(name)
+        })
+        .map(|(key, data)| Candidate {
+            key: key.clone(),
+            params: data
+                .borrow()
+                .table
+                .as_ref()
+                .map(|t| t.params.clone())
+                .unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| a.key.cmp(&b.key));
+
+    candidates
+}
+
+/// How far `args` are from `params`, `None` if they can't be passed at all
+fn call_cost(params: &[VarType], args: &[Option<VarType>]) -> Option<usize> {
+    if params.len() != args.len() {
+        return None;
+    }
+
+    params
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| match arg {
+            Some(VarType::Error) | None => Some(1),
+            Some(arg) => argument_cost(param, arg),
+        })
+        .sum()
+}
+
+fn argument_cost(param: &VarType, arg: &VarType) -> Option<usize> {
+    if param == arg {
+        return Some(0);
+    }
+
+    let same_base = match (param, arg) {
+        (VarType::Integer(_), VarType::Integer(_)) | (VarType::Float(_), VarType::Float(_)) => true,
+        (VarType::Class(p, _), VarType::Class(a, _)) => p == a,
+        _ => false,
+    };
+
+    let (param_dims, arg_dims) = (param.dimensions(), arg.dimensions());
+    let dims_match = param_dims.len() == arg_dims.len()
+        && param_dims
+            .iter()
+            .zip(arg_dims)
+            .all(|(p, a)| *p == 0 || p == a);
+
+    (same_base && dims_match).then_some(1)
+}
+
+fn list_keys<'a>(candidates: impl Iterator<Item = &'a Candidate>) -> String {
+    candidates
+        .map(|c| format!("'{}'", c.key))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
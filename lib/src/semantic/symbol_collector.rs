@@ -2,14 +2,18 @@ use std::{cell::RefCell, rc::Rc};
 
 use crate::{
     ast::{
-        nodes::{CodeNode, NodeValue, SymbolData, SymbolTable, VarType},
+        nodes::{CodeNode, NodeValue, VarType},
+        symbol_table::{ScopeKind, SymbolData, SymbolKind, SymbolTable},
         tree_node::TreeNode,
     },
     compiler_error::CompilerError,
     lexical::tokens::token_type::Type,
 };
 
-use super::visitor::{Visitor, VisitorResult};
+use super::{
+    visitor::{Visitor, VisitorResult},
+    visitor_utils::{get_declared_location, with_global_table},
+};
 
 #[derive(Default)]
 pub struct SymbolCollectorVisitor {
@@ -40,6 +44,7 @@ impl Visitor for SymbolCollectorVisitor {
                 "No main function found! (Did you make a typo or add parameters?)".to_string(),
                 node_ref.token.clone(),
             )
+            .with_code("E0105")
         })?;
 
         Ok(())
@@ -75,31 +80,29 @@ impl Visitor for SymbolCollectorVisitor {
                 ),
                 node.borrow().token.clone(),
             )
+            .with_code("E0101")
             .into());
         }
 
-        let size = table.values().fold(0, |acc, x| acc + x.borrow().size);
-
-        table.insert(
-            "..".to_string(),
-            Rc::new(RefCell::new(SymbolData::new_with_table(
-                0,
-                0,
-                VarType::Global,
-                self.global.clone(),
-            ))),
-        );
+        // The layout pass already sized the class, inherited attributes included
+        let size = with_global_table(node, |global| {
+            global
+                .get(&class_name)
+                .map_or(0, |class| class.borrow().size)
+        })?;
 
         // Then add the class to the global table:
-        self.global.insert(
-            class_name.clone(),
-            Rc::new(RefCell::new(SymbolData::new_with_table(
-                size,
-                0,
-                VarType::Class(class_name),
-                table.clone(),
-            ))),
+        let mut symbol_data = SymbolData::new_with_table(
+            SymbolKind::Class,
+            size,
+            0,
+            VarType::Class(class_name.clone(), vec![]),
+            table.clone(),
         );
+        symbol_data.location = get_declared_location(node);
+
+        self.global
+            .insert(class_name, Rc::new(RefCell::new(symbol_data)));
 
         Ok(())
     }
@@ -114,55 +117,34 @@ impl Visitor for SymbolCollectorVisitor {
 
         let mut errors: Vec<CompilerError> = vec![];
 
-        let (func_name, _) = head_table
-            .as_ref()
-            .unwrap()
-            .iter()
-            .find(|(_, value)| value.borrow().var_type == VarType::Function)
-            .ok_or(CompilerError::new(
-                "Function not found in head table".to_string(),
-                node_ref.token.clone(),
-            ))?;
-
-        let (return_name, return_type) = head_table
-            .as_ref()
-            .unwrap()
-            .iter()
-            .find(|(k, _)| k == &"_return")
-            .ok_or(CompilerError::new(
-                "Function return type not found in head table".to_string(),
-                node_ref.token.clone(),
-            ))?;
-
-        table.insert(return_name.clone(), return_type.clone());
-        table.insert(
-            "_ret_addr".to_string(),
-            Rc::new(RefCell::new(SymbolData::new(4, 0, VarType::Void))),
-        );
+        let head_table = head_table.as_ref().unwrap();
 
-        if (self.global).contains_key(func_name) {
-            errors.push(CompilerError::new(
-                format!(
-                    "Function '{}' already defined! Defined again at {}",
-                    func_name, node
-                ),
-                node_ref.token.clone(),
-            ));
-        }
+        let func_name = match &head_table.scope {
+            ScopeKind::Function(func_name) => func_name,
+            _ => {
+                return Err(CompilerError::new(
+                    "Function not found in head table".to_string(),
+                    node_ref.token.clone(),
+                )
+                .into())
+            }
+        };
 
-        let func_split = func_name.split_once('(').map(|v| v.0);
-        for (key, value) in self.global.clone() {
-            let val_type = value.borrow().var_type.clone();
+        table.scope = head_table.scope.clone();
+        table.return_type = head_table.return_type.clone();
+        table.params = head_table.params.clone();
 
-            if val_type == VarType::Function
-                && key.split_once('(').map(|v| v.0) == func_split
-                && &key != func_name
-            {
-                errors.push(CompilerError::new(
-                    format!("[WARN] Function '{func_name}' is overloading function '{key}'",),
+        if (self.global).contains_key(func_name) {
+            errors.push(
+                CompilerError::new(
+                    format!(
+                        "Function '{}' already defined! Defined again at {}",
+                        func_name, node
+                    ),
                     node_ref.token.clone(),
-                ));
-            }
+                )
+                .with_code("E0102"),
+            );
         }
 
         let param_list = head.children().nth(1).unwrap();
@@ -179,13 +161,16 @@ impl Visitor for SymbolCollectorVisitor {
                     let (key, value) = var_table.iter().next().unwrap();
 
                     if table.contains_key(key) {
-                        errors.push(CompilerError::new(
-                            format!(
-                                "Variable '{}' already defined in '{}'! Defined again at {}",
-                                key, func_name, child
-                            ),
-                            child.borrow().token.clone(),
-                        ));
+                        errors.push(
+                            CompilerError::new(
+                                format!(
+                                    "Variable '{}' already defined in '{}'! Defined again at {}",
+                                    key, func_name, child
+                                ),
+                                child.borrow().token.clone(),
+                            )
+                            .with_code("E0103"),
+                        );
                     };
 
                     size += value.borrow().size;
@@ -196,41 +181,39 @@ impl Visitor for SymbolCollectorVisitor {
             }
         }
 
-        // Resolve class sizes
+        // Member functions get a pointer to the object they're called on
+        if let Some(self_data) = head_table.get("self") {
+            size += self_data.borrow().size;
+            table.insert("self".to_string(), self_data.clone());
+        }
+
+        // Link objects to their class table, their size is already known from the class layout
         for (key, value) in table.clone().iter() {
             let value_ref = value.borrow();
-            let var_type = value_ref.var_type.clone();
-
-            if let VarType::Class(class_name) = var_type.clone() {
-                let class_data = self.global.get(&class_name).unwrap();
-                let class_size = class_data.borrow().size;
-
-                table.insert(
-                    key.clone(),
-                    Rc::new(RefCell::new(SymbolData::new_with_table(
-                        class_size,
-                        value_ref.offset,
-                        var_type,
-                        class_data.borrow().table.clone().unwrap(),
-                    ))),
-                );
-
-                for (key2, value2) in table.clone() {
-                    if value2.borrow().offset > value_ref.offset || &key2 == key {
-                        continue;
-                    }
 
-                    let mut value2_ref = value2.borrow().clone();
-                    value2_ref.offset -= class_size as isize;
-                    table.insert(key2.clone(), Rc::new(RefCell::new(value2_ref)));
-                }
+            // self is only a pointer, the object lives in the caller's frame
+            if key == "self" {
+                continue;
+            }
+
+            if let VarType::Class(class_name, _) = &value_ref.var_type {
+                let class_data = match self.global.get(class_name) {
+                    Some(class_data) => class_data,
+                    None => continue,
+                };
+
+                let mut value_data = value_ref.clone();
+                value_data.table = class_data.borrow().table.clone();
+                table.insert(key.clone(), Rc::new(RefCell::new(value_data)));
             }
         }
 
         let head_id_node = head.children().next().unwrap();
 
-        let (var_type, parent_table) =
-            if let NodeValue::Tree(TreeNode::Scope()) = head_id_node.borrow().value.clone() {
+        // Members of unknown classes are reported by the member definitions pass, they just get
+        // the global scope here
+        let scope_class = match head_id_node.borrow().value.clone() {
+            NodeValue::Tree(TreeNode::Scope()) => {
                 let scope_id = head_id_node.children().next().unwrap();
                 let scope_id = match scope_id.borrow().value.clone() {
                     NodeValue::Leaf(Type::Id(id)) => id,
@@ -243,26 +226,26 @@ impl Visitor for SymbolCollectorVisitor {
                     }
                 };
 
-                let var_data = self.global.get(&scope_id).unwrap();
-                let var_type = VarType::Class(scope_id);
-                (var_type, var_data.borrow().clone().table.unwrap())
-            } else {
-                (VarType::Global, self.global.clone())
-            };
-
-        table.insert(
-            "..".to_string(),
-            Rc::new(RefCell::new(SymbolData::new_with_table(
-                0,
-                0,
-                var_type,
-                parent_table,
-            ))),
-        );
+                self.global.contains_key(&scope_id).then_some(scope_id)
+            }
+            _ => None,
+        };
 
-        let mut symbol_data = SymbolData::new_with_table(size, 0, VarType::Function, table.clone());
+        table.parent = Some(match scope_class {
+            Some(class_name) => ScopeKind::Class(class_name),
+            None => ScopeKind::Global,
+        });
+
+        let mut symbol_data = SymbolData::new_with_table(
+            SymbolKind::Function,
+            size,
+            0,
+            VarType::Function,
+            table.clone(),
+        );
         let func_label = head_ref.label.borrow();
         symbol_data.label = func_label.clone();
+        symbol_data.location = get_declared_location(&head);
 
         // Then add the func to the global table:
         self.global
@@ -7,16 +7,20 @@ use std::{
 
 use crate::{
     ast::{
-        nodes::{CodeNode, NodeValue, SymbolData, SymbolTable, VarType, Visibility},
+        nodes::{CodeNode, NodeValue, VarType},
+        symbol_table::{ScopeKind, SymbolData, SymbolKind, SymbolTable, Visibility},
         tree_node::TreeNode,
     },
-    compiler_error::CompilerError,
+    compiler_error::{CompilerError, CompilerResult},
     lexical::tokens::token_type::Type,
 };
 
 use super::{
     symbol_visitor::get_type_size,
     visitor::{Visitor, VisitorResult},
+    visitor_utils::{
+        get_declared_dimensions, get_declared_location, get_param_types, member_name, Signature,
+    },
 };
 
 const BASE_OFFSET: isize = 4;
@@ -32,7 +36,7 @@ impl SymbolGlobalResolverVisitor {
         Self {
             global: Default::default(),
             offset: AtomicIsize::new(BASE_OFFSET),
-            current_table: Rc::new(RefCell::new(SymbolTable::new())),
+            current_table: Rc::new(RefCell::new(SymbolTable::default())),
         }
     }
 }
@@ -116,28 +120,25 @@ impl Visitor for SymbolGlobalResolverVisitor {
         let mut table_ref = node_ref.symbol_table.borrow_mut();
         let table = table_ref.get_or_insert_with(Default::default);
 
-        table.insert(
-            "_inherits".to_string(),
-            Rc::new(RefCell::new(SymbolData::new(
-                0,
-                0,
-                VarType::Inherits(inherits_list),
-            ))),
-        );
+        table.scope = ScopeKind::Class(class_name.clone());
+        table.parent = Some(ScopeKind::Global);
+        table.bases = inherits_list;
 
         if let Some(members_table) = members.borrow().symbol_table.borrow().clone() {
             table.extend(members_table);
         }
 
-        self.global.insert(
-            class_name.clone(),
-            Rc::new(RefCell::new(SymbolData::new_with_table(
-                0,
-                0,
-                VarType::Class(class_name),
-                table.clone(),
-            ))),
+        let mut symbol_data = SymbolData::new_with_table(
+            SymbolKind::Class,
+            0,
+            0,
+            VarType::Class(class_name.clone(), vec![]),
+            table.clone(),
         );
+        symbol_data.location = get_declared_location(node);
+
+        self.global
+            .insert(class_name, Rc::new(RefCell::new(symbol_data)));
 
         Ok(())
     }
@@ -148,24 +149,15 @@ impl Visitor for SymbolGlobalResolverVisitor {
         let table = table_ref.get_or_insert_with(Default::default);
         let mut errors: Vec<CompilerError> = vec![];
         let mut added_members: Vec<String> = vec![];
+        // Attributes are laid out in the order they're declared, going down from the object's address
+        let mut attribute_offset: isize = 0;
 
         for member in members {
             let res: Result<(usize, String, VarType, Option<Visibility>), CompilerError> =
                 match member.borrow().value {
                     NodeValue::Tree(TreeNode::Attribute()) => {
-                        let mut children = member.children();
-                        let visibility: Type = children.next().unwrap().try_into()?;
-                        let visibility = match visibility {
-                            Type::Public => Visibility::Public,
-                            Type::Private => Visibility::Private,
-                            _ => {
-                                return Err(CompilerError::new(
-                                    format!("Expected visibility at '{}'!", member.borrow().value),
-                                    member.borrow().token.clone(),
-                                )
-                                .into())
-                            }
-                        };
+                        let visibility = get_visibility(&member)?;
+                        let mut children = member.children().skip(1);
                         let id: Type = children.next().unwrap().try_into()?;
 
                         let id = if let Type::Id(n) = id {
@@ -180,26 +172,7 @@ impl Visitor for SymbolGlobalResolverVisitor {
 
                         let type_: Type = children.next().unwrap().try_into()?;
 
-                        let indices = children
-                            .next()
-                            .unwrap()
-                            .children()
-                            .map(|num| -> Result<usize, CompilerError> {
-                                if let NodeValue::Leaf(Type::IntNum(n)) = &num.borrow().value {
-                                    usize::try_from(*n).map_err(|_| {
-                                        CompilerError::new(
-                                            "Expected usize!".to_string(),
-                                            num.borrow().token.clone(),
-                                        )
-                                    })
-                                } else {
-                                    Err(CompilerError::new(
-                                        "Expected number!".to_string(),
-                                        num.borrow().token.clone(),
-                                    ))
-                                }
-                            })
-                            .collect::<Result<Vec<_>, _>>()?;
+                        let indices = get_declared_dimensions(&children.next().unwrap(), false)?;
 
                         let indice_mult: usize = indices.iter().product();
                         let size = get_type_size(&type_) * indice_mult.max(1);
@@ -210,7 +183,7 @@ impl Visitor for SymbolGlobalResolverVisitor {
                             VarType::Float(indices)
                         } else {
                             match type_ {
-                                Type::Id(class_name) => VarType::Class(class_name),
+                                Type::Id(class_name) => VarType::Class(class_name, indices),
                                 _ => {
                                     return Err(CompilerError::new(
                                         format!(
@@ -227,27 +200,24 @@ impl Visitor for SymbolGlobalResolverVisitor {
                         Ok((size, id, var_type, Some(visibility)))
                     }
                     NodeValue::Tree(TreeNode::ConstructorFunc()) => {
-                        let mut children = member.children();
-                        let _visibility: Type = children.next().unwrap().try_into()?;
-                        let id: Type = children.next().unwrap().try_into()?;
-                        let _param_list: Vec<String> = children
-                            .next()
-                            .unwrap()
-                            .children()
-                            .map(|c| {
-                                let mut children = c.children();
-                                let type_: Type = children.nth(1).unwrap().try_into()?;
-                                let indices = children.next().unwrap().children().count();
-
-                                Ok(format!("{}[{}]", type_, indices))
-                            })
-                            .collect::<Result<Vec<_>, CompilerError>>()?;
-
-                        Ok((0, id.to_string(), VarType::Function, None))
+                        let visibility = get_visibility(&member)?;
+                        let mut children = member.children().skip(1);
+                        let _id: Type = children.next().unwrap().try_into()?;
+                        let param_list = children.next().unwrap();
+
+                        // Constructors can be overloaded, so they're keyed by their parameters
+                        let signature = Signature::from_nodes("constructor", &param_list, None);
+
+                        Ok((
+                            0,
+                            signature.to_string(),
+                            VarType::Function,
+                            Some(visibility),
+                        ))
                     }
                     NodeValue::Tree(TreeNode::MemberFunc()) => {
-                        let mut children = member.children();
-                        let _visibility: Type = children.next().unwrap().try_into()?;
+                        let visibility = get_visibility(&member)?;
+                        let mut children = member.children().skip(1);
                         let id: Type = children.next().unwrap().try_into()?;
                         let id = if let Type::Id(n) = id {
                             n
@@ -258,21 +228,18 @@ impl Visitor for SymbolGlobalResolverVisitor {
                             )
                             .into());
                         };
-                        let _param_list: Vec<String> = children
-                            .next()
-                            .unwrap()
-                            .children()
-                            .map(|c| {
-                                let mut children = c.children();
-                                let type_: Type = children.nth(1).unwrap().try_into()?;
-                                let indices = children.next().unwrap().children().count();
-
-                                Ok(format!("{}[{}]", type_, indices))
-                            })
-                            .collect::<Result<Vec<_>, CompilerError>>()?;
+                        let param_list = children.next().unwrap();
                         let _return_type: Type = children.next().unwrap().try_into()?;
 
-                        Ok((0, id, VarType::Function, None))
+                        // Member functions can be overloaded too
+                        let signature = Signature::from_nodes(&id, &param_list, None);
+
+                        Ok((
+                            0,
+                            signature.to_string(),
+                            VarType::Function,
+                            Some(visibility),
+                        ))
                     }
                     _ => {
                         return Err(CompilerError::new(
@@ -287,15 +254,42 @@ impl Visitor for SymbolGlobalResolverVisitor {
                 };
 
             let (size, key, var_type, visibility) = res?;
-            if added_members.contains(&key) {
-                errors.push(CompilerError::new(
-                    format!("Duplicate member '{}'!", key),
-                    member.borrow().token.clone(),
-                ));
+            // Overloads are keyed by signature, but an attribute can't share a member function's name
+            if added_members.iter().any(|added| {
+                *added == key
+                    || (added.contains('(') != key.contains('('))
+                        && member_name(added) == member_name(&key)
+            }) {
+                errors.push(
+                    CompilerError::new(
+                        format!("Duplicate member '{}'!", key),
+                        member.borrow().token.clone(),
+                    )
+                    .with_code("E0104"),
+                );
             } else {
                 added_members.push(key.clone());
-                let mut symbol_data = SymbolData::new(size, 0, var_type);
+
+                let (kind, offset) = if var_type == VarType::Function {
+                    (SymbolKind::Function, 0)
+                } else {
+                    let offset = attribute_offset;
+                    attribute_offset -= size as isize;
+                    (SymbolKind::Attribute, offset)
+                };
+
+                let mut symbol_data = SymbolData::new(kind, size, offset, var_type);
                 symbol_data.visibility = visibility;
+                symbol_data.location = get_declared_location(&member);
+                // The parameters come after the visibility and the name
+                if kind == SymbolKind::Function {
+                    if let Some(param_list) = member.children().nth(2) {
+                        symbol_data.params = get_param_types(&param_list)
+                            .into_iter()
+                            .map(|param| param.unwrap_or(VarType::Error))
+                            .collect();
+                    }
+                }
                 table.insert(key, Rc::new(RefCell::new(symbol_data)));
             }
         }
@@ -331,24 +325,7 @@ impl Visitor for SymbolGlobalResolverVisitor {
 
         let var_key = match &indices.borrow().value {
             NodeValue::Tree(TreeNode::IndiceList()) => {
-                let indices = indices
-                    .children()
-                    .map(|num| -> Result<usize, CompilerError> {
-                        if let NodeValue::Leaf(Type::IntNum(n)) = &num.borrow().value {
-                            usize::try_from(*n).map_err(|_| {
-                                CompilerError::new(
-                                    "Expected usize!".to_string(),
-                                    num.borrow().token.clone(),
-                                )
-                            })
-                        } else {
-                            Err(CompilerError::new(
-                                "Expected number!".to_string(),
-                                num.borrow().token.clone(),
-                            ))
-                        }
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
+                let indices = get_declared_dimensions(&indices, true)?;
 
                 let indice_mult: usize = indices.iter().product();
                 size = get_type_size(&type_) * indice_mult.max(1);
@@ -360,7 +337,7 @@ impl Visitor for SymbolGlobalResolverVisitor {
                     VarType::Float(indices)
                 } else {
                     match type_ {
-                        Type::Id(class_name) => VarType::Class(class_name),
+                        Type::Id(class_name) => VarType::Class(class_name, indices),
                         _ => {
                             return Err(CompilerError::new(
                                 format!("Expected class identifier at '{}'!", var_name),
@@ -382,8 +359,9 @@ impl Visitor for SymbolGlobalResolverVisitor {
             }
         };
 
-        let mut symbol_data = SymbolData::new(size, offset, var_type);
+        let mut symbol_data = SymbolData::new(SymbolKind::Parameter, size, offset, var_type);
         symbol_data.label.replace(format!("{offset}(r14)"));
+        symbol_data.location = get_declared_location(node);
 
         let node_ref = node.borrow();
         let mut table_ref = node_ref.symbol_table.borrow_mut();
@@ -431,8 +409,12 @@ impl Visitor for SymbolGlobalResolverVisitor {
                     let mut id_str: Vec<String> = vec![];
 
                     for child in id.children() {
-                        if let NodeValue::Leaf(Type::Id(id)) = &child.borrow().value {
-                            id_str.push(id.clone());
+                        match &child.borrow().value {
+                            NodeValue::Leaf(Type::Id(id)) => id_str.push(id.clone()),
+                            NodeValue::Leaf(Type::Constructor) => {
+                                id_str.push(String::from("constructor"))
+                            }
+                            _ => (),
                         }
                     }
 
@@ -466,7 +448,13 @@ impl Visitor for SymbolGlobalResolverVisitor {
             table.extend(param_table);
         }
 
-        fmt_params.sort_by(|a, b| b.borrow().offset.cmp(&a.borrow().offset));
+        fmt_params.sort_by_key(|p| std::cmp::Reverse(p.borrow().offset));
+
+        table.params = fmt_params
+            .iter()
+            .map(|p| p.borrow().var_type.clone())
+            .collect();
+
         let fmt_params = fmt_params
             .iter()
             .map(|s| s.borrow().var_type.to_string())
@@ -474,32 +462,36 @@ impl Visitor for SymbolGlobalResolverVisitor {
             .join(", ");
         let func_signature = format!("{func_name}({fmt_params})");
 
-        if let Some(t) = return_type {
-            let return_size = get_type_size(&t);
-
-            table.insert(
-                String::from("_return"),
-                Rc::new(RefCell::new(SymbolData::new(
-                    return_size,
-                    0,
-                    match t {
-                        Type::Integer => VarType::Integer(vec![]),
-                        Type::Float => VarType::Float(vec![]),
-                        Type::Id(id) => VarType::Class(id),
-                        Type::Void => VarType::Void,
-                        _ => {
-                            return Err(CompilerError::new(
-                                format!("Expected type at '{}'!", node.borrow().value),
-                                node.borrow().token.clone(),
-                            )
-                            .into())
-                        }
-                    },
-                ))),
-            );
-        }
-
-        let symbol_data = SymbolData::new_with_table(size, 0, VarType::Function, table.clone());
+        // Member functions are in the scope of their class
+        table.scope = ScopeKind::Function(func_signature.clone());
+        table.parent = Some(match func_name.split_once("::") {
+            Some((class_name, _)) => ScopeKind::Class(class_name.to_string()),
+            None => ScopeKind::Global,
+        });
+
+        // Constructors don't return anything
+        table.return_type = Some(match return_type.unwrap_or(Type::Void) {
+            Type::Integer => VarType::Integer(vec![]),
+            Type::Float => VarType::Float(vec![]),
+            Type::Id(id) => VarType::Class(id, vec![]),
+            Type::Void => VarType::Void,
+            _ => {
+                return Err(CompilerError::new(
+                    format!("Expected type at '{}'!", node.borrow().value),
+                    node.borrow().token.clone(),
+                )
+                .into())
+            }
+        });
+
+        let mut symbol_data = SymbolData::new_with_table(
+            SymbolKind::Function,
+            size,
+            0,
+            VarType::Function,
+            table.clone(),
+        );
+        symbol_data.location = get_declared_location(node);
 
         // Then add the func to the global table:
         self.global
@@ -508,3 +500,16 @@ impl Visitor for SymbolGlobalResolverVisitor {
         Ok(())
     }
 }
+
+/// The visibility a class member is declared with, its first child
+fn get_visibility(member: &CodeNode) -> CompilerResult<Visibility> {
+    match member.first_child().map(|v| v.borrow().value.clone()) {
+        Some(NodeValue::Leaf(Type::Public)) => Ok(Visibility::Public),
+        Some(NodeValue::Leaf(Type::Private)) => Ok(Visibility::Private),
+        _ => Err(CompilerError::new(
+            format!("Expected visibility at '{}'!", member.borrow().value),
+            member.borrow().token.clone(),
+        )
+        .into()),
+    }
+}
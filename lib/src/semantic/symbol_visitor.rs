@@ -6,22 +6,31 @@ use std::{
 
 use crate::{
     ast::{
-        nodes::{CodeNode, NodeValue, SymbolData, SymbolTable, VarType, Visibility},
+        nodes::{CodeNode, NodeValue, StructNode, VarType},
+        symbol_table::{ScopeKind, SymbolData, SymbolKind, SymbolTable, Visibility},
         tree_node::TreeNode,
     },
-    compiler_error::CompilerError,
+    compiler_error::{CompilerError, CompilerResult},
     lexical::tokens::token_type::Type,
 };
 
 use super::{
-    visitor::{Visitor, VisitorResult, FLOAT_SIZE, INT_SIZE},
-    visitor_utils::get_global_table,
+    constants::{const_int, evaluate, Constant},
+    overloads::{resolve_constructor, resolve_overload},
+    visitor::{EnterResult, Traversal, Visitor, VisitorResult, FLOAT_SIZE, INT_SIZE},
+    visitor_utils::{
+        get_class_member, get_current_class, get_current_function, get_current_function_name,
+        get_declared_dimensions, get_declared_location, with_global_table,
+    },
+    xref::{SymbolId, SymbolReference},
 };
 
 pub struct SymbolTableVisitor {
     offset: AtomicIsize,
     label_count: AtomicUsize,
     current_table: Rc<RefCell<SymbolTable>>,
+    /// What every variable, member and call resolved to, for the `XrefIndex`
+    pub references: Vec<SymbolReference>,
 }
 
 const BASE_OFFSET: isize = 4;
@@ -31,128 +40,218 @@ impl SymbolTableVisitor {
         Self {
             offset: AtomicIsize::new(BASE_OFFSET),
             label_count: AtomicUsize::new(0),
-            current_table: Rc::new(RefCell::new(SymbolTable::new())),
+            current_table: Rc::new(RefCell::new(SymbolTable::default())),
+            references: vec![],
         }
     }
 
-    pub fn new_func_label(&self) -> String {
-        format!("f{}", self.label_count.fetch_add(1, Ordering::SeqCst))
-    }
-}
+    /// Record that the identifier `node`, or the identifier of `node` (a variable, member or
+    /// call), refers to `symbol`
+    fn add_reference(&mut self, node: &CodeNode, symbol: SymbolId) {
+        let id = std::iter::once(node.clone())
+            .chain(node.children())
+            .find(|c| matches!(c.borrow().value, NodeValue::Leaf(Type::Id(_))));
+
+        // Only assigned to if it's what `a = ...` or `read(a)` changes as a whole, elements of an
+        // array parameter are the caller's
+        let is_write = node.previous_sibling().is_none()
+            && node.parent().is_some_and(|parent| {
+                matches!(
+                    parent.borrow().value,
+                    NodeValue::Tree(TreeNode::Assignment() | TreeNode::Read())
+                )
+            })
+            && node.last_child().is_some_and(|indices| {
+                matches!(
+                    indices.borrow().value,
+                    NodeValue::Tree(TreeNode::IndiceList())
+                ) && indices.first_child().is_none()
+            });
 
-impl Default for SymbolTableVisitor {
-    fn default() -> Self {
-        Self::new()
+        if let Some(id) = id {
+            let span = id.borrow().span.clone();
+            self.references.push(SymbolReference {
+                symbol,
+                span,
+                is_write,
+            });
+        }
     }
-}
 
-impl Visitor for SymbolTableVisitor {
-    fn visit_class(
+    /// Pick the constructor of `class_name` that builds the object `node` declares with the
+    /// arguments in `arg_list`
+    fn check_constructor_call(
         &mut self,
         node: &CodeNode,
-        id: Type,
-        _inherits: CodeNode,
-        _members: CodeNode,
+        global: &SymbolTable,
+        class_name: &str,
+        arg_list: &CodeNode,
     ) -> VisitorResult {
-        // Reset the offset counter
-        self.offset.store(BASE_OFFSET, Ordering::SeqCst);
+        let node_ref = node.borrow();
+        let no_table = SymbolTable::default();
+        let class_data = global.get(class_name).map(|class_data| class_data.borrow());
+        let class_table = class_data
+            .as_ref()
+            .and_then(|class_data| class_data.table.as_ref())
+            .unwrap_or(&no_table);
+        let args = arg_list
+            .children()
+            .map(|c| c.borrow().var_type.borrow().clone())
+            .collect::<Vec<_>>();
+
+        let declares_constructors = class_table
+            .keys()
+            .any(|key| key.starts_with("constructor("));
+
+        if declares_constructors {
+            // Checked against what the class declares, a constructor that's declared but not
+            // defined is reported with the class
+            let target = match resolve_constructor(class_table, class_name, &args) {
+                Ok(target) => target,
+                Err(e) if e.code == "E0205" && args.iter().any(is_poisoned) => return Ok(()),
+                Err(e) => return Err(e.into_error(&node_ref).into()),
+            };
 
-        let class_name = match id {
-            Type::Id(id) => id,
-            _ => {
-                return Err(CompilerError::new(
-                    format!("Expected identifier at '{}'!", node.borrow().value),
-                    node.borrow().token.clone(),
-                )
-                .into())
+            let signature = target.trim_start_matches(&format!("{class_name}::"));
+            if let Some(data) = class_table.get(signature) {
+                check_access(node, class_name, signature, data)?;
             }
-        };
 
-        let node_ref = node.borrow();
-        let mut table_ref = node_ref.symbol_table.borrow_mut();
-        let table = table_ref.get_or_insert_with(Default::default);
+            if global.contains_key(&target) {
+                // The class name is where the constructor is called
+                if let Some(class_id) = node.children().nth(1) {
+                    self.add_reference(&class_id, SymbolId::new(ScopeKind::Global, &target));
+                }
 
-        let inherit_list = table.get("_inherits").unwrap().clone();
-        let inherit_list = inherit_list.borrow();
-        let mut inherit_list = match inherit_list.var_type.clone() {
-            VarType::Inherits(list) => list,
-            _ => unreachable!(),
-        };
+                node_ref.target.borrow_mut().replace(target);
+            }
+        } else if !args.is_empty() {
+            return Err(CompilerError::new_with_node(
+                format!("Class '{class_name}' doesn't declare any constructors!"),
+                &node_ref,
+            )
+            .with_code("E0208")
+            .into());
+        }
 
-        let global_table = get_global_table(node).unwrap();
+        Ok(())
+    }
 
-        // Add inherited classes to the table
-        for inherit in inherit_list.clone() {
-            let other_class = global_table.get(&inherit).ok_or_else(|| {
-                CompilerError::new(
-                    format!("Class '{}' not found!", inherit),
-                    node_ref.token.clone(),
-                )
-            })?;
+    /// Resolve the function `node` calls, on `object` if it's a member function
+    fn check_call(
+        &mut self,
+        node: &CodeNode,
+        global: &SymbolTable,
+        object: Option<CodeNode>,
+        func_name: String,
+        args: Vec<Option<VarType>>,
+    ) -> VisitorResult {
+        let node_ref = node.borrow();
 
-            table.insert(format!("_in_{inherit}"), other_class.clone());
-        }
+        // The class that declares the member function called, if it is one
+        let owner = match object {
+            Some(object) => {
+                let (owner, member_data) = match resolve_member(node, &object, &func_name)
+                    .inspect_err(|_| poison(node))?
+                {
+                    Some(resolved) => resolved,
+                    None => {
+                        poison(node);
+                        return Ok(());
+                    }
+                };
 
-        while let Some(inherit) = inherit_list.pop() {
-            let other_class = global_table.get(&inherit).ok_or_else(|| {
-                CompilerError::new(
-                    format!("Class '{}' not found!", inherit),
-                    node_ref.token.clone(),
-                )
-            })?;
+                if member_data.borrow().var_type != VarType::Function {
+                    poison(node);
+                    return Err(CompilerError::new_with_node(
+                        format!("'{owner}::{func_name}' is an attribute, not a member function!"),
+                        &node_ref,
+                    )
+                    .with_code("E0211")
+                    .into());
+                }
 
-            let other_table = other_class.borrow();
-            let other_table = other_table.table.clone().ok_or_else(|| {
-                CompilerError::new(
-                    format!("Class '{}' does not have a symbol table!", inherit),
-                    node_ref.token.clone(),
-                )
-            })?;
+                Some(owner)
+            }
+            // Inside member functions, other members can be called without `self.`
+            None => get_current_class(node)
+                .and_then(|class_name| get_class_member(global, &class_name, &func_name))
+                .filter(|(_, member_data)| member_data.borrow().var_type == VarType::Function)
+                .map(|(owner, _)| owner),
+        };
+        let name = match &owner {
+            Some(owner) => format!("{owner}::{func_name}"),
+            None => func_name,
+        };
 
-            let other_type = other_table
-                .get("_inherits")
-                .ok_or_else(|| {
-                    CompilerError::new(
-                        format!("Class '{}' does not have an inherit list!", inherit),
-                        node_ref.token.clone(),
-                    )
-                })?
-                .borrow()
-                .var_type
-                .clone();
-
-            let other_list = match other_type {
-                VarType::Inherits(table) => table,
-                _ => unreachable!(),
+        let target =
+            match resolve_call(global, &name, &args, &node_ref).inspect_err(|_| poison(node))? {
+                Some(target) => target,
+                None => {
+                    poison(node);
+                    return Ok(());
+                }
             };
 
-            if other_list.contains(&class_name) {
-                return Err(CompilerError::new(
-                    format!(
-                        "Circular inheritance detected! Class '{class_name}' inherits from itself!",
-                    ),
-                    node_ref.token.clone(),
-                )
-                .into());
+        // Overloads can have different visibilities, so only the one called is checked
+        if let Some(owner) = &owner {
+            let signature = target.trim_start_matches(&format!("{owner}::")).to_string();
+            if let Some((_, member_data)) = get_class_member(global, owner, &signature) {
+                check_access(node, owner, &signature, &member_data)
+                    .inspect_err(|_| poison(node))?;
             }
-
-            inherit_list.extend(other_list);
         }
 
+        let return_type = global[target.as_str()]
+            .borrow()
+            .table
+            .as_ref()
+            .and_then(|func_table| func_table.return_type.clone())
+            .ok_or(CompilerError::new(
+                "Function return type not found in head table".to_string(),
+                node_ref.token.clone(),
+            ))?;
+
+        self.add_reference(node, SymbolId::new(ScopeKind::Global, &target));
+
+        node_ref.target.borrow_mut().replace(target);
+        node_ref.var_type.borrow_mut().replace(return_type);
+
         Ok(())
     }
 
-    fn visit_function(
+    pub fn new_func_label(&self) -> String {
+        format!("f{}", self.label_count.fetch_add(1, Ordering::SeqCst))
+    }
+}
+
+impl Default for SymbolTableVisitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Visitor for SymbolTableVisitor {
+    fn visit_class(
         &mut self,
         _node: &CodeNode,
-        _head: CodeNode,
-        _body: CodeNode,
+        _id: Type,
+        _inherits: CodeNode,
+        _members: CodeNode,
     ) -> VisitorResult {
+        // Reset the offset counter
         self.offset.store(BASE_OFFSET, Ordering::SeqCst);
-        self.current_table.replace(SymbolTable::new());
+
         Ok(())
     }
 
+    fn enter_function(&mut self, _node: &CodeNode) -> EnterResult {
+        // Every function starts with a fresh frame
+        self.offset.store(BASE_OFFSET, Ordering::SeqCst);
+        self.current_table.replace(SymbolTable::default());
+        Ok(Traversal::Descend)
+    }
+
     fn visit_parameter(
         &mut self,
         node: &CodeNode,
@@ -177,27 +276,14 @@ impl Visitor for SymbolTableVisitor {
 
         let var_key = match &indices.borrow().value {
             NodeValue::Tree(TreeNode::IndiceList()) => {
-                let indices = indices
-                    .children()
-                    .map(|num| -> Result<usize, CompilerError> {
-                        if let NodeValue::Leaf(Type::IntNum(n)) = &num.borrow().value {
-                            usize::try_from(*n).map_err(|_| {
-                                CompilerError::new(
-                                    "Expected usize!".to_string(),
-                                    num.borrow().token.clone(),
-                                )
-                            })
-                        } else {
-                            Err(CompilerError::new(
-                                "Expected number!".to_string(),
-                                num.borrow().token.clone(),
-                            ))
-                        }
-                    })
-                    .collect::<Result<Vec<_>, _>>()?;
+                let indices = get_declared_dimensions(&indices, true)?;
 
-                let indice_mult: usize = indices.iter().product();
-                size = get_type_size(&type_) * indice_mult.max(1);
+                // Arrays are passed by reference, the parameter only holds their address
+                size = if indices.is_empty() {
+                    get_var_size(node, &type_)
+                } else {
+                    INT_SIZE
+                };
                 offset = -self.offset.fetch_add(size as isize, Ordering::SeqCst);
 
                 var_type = if type_ == Type::Integer {
@@ -206,7 +292,7 @@ impl Visitor for SymbolTableVisitor {
                     VarType::Float(indices)
                 } else {
                     match type_ {
-                        Type::Id(class_name) => VarType::Class(class_name),
+                        Type::Id(class_name) => VarType::Class(class_name, indices),
                         _ => {
                             return Err(CompilerError::new(
                                 format!("Expected class identifier at '{}'!", var_name),
@@ -228,8 +314,9 @@ impl Visitor for SymbolTableVisitor {
             }
         };
 
-        let mut symbol_data = SymbolData::new(size, offset, var_type);
+        let mut symbol_data = SymbolData::new(SymbolKind::Parameter, size, offset, var_type);
         symbol_data.label.replace(format!("{offset}(r14)"));
+        symbol_data.location = get_declared_location(node);
 
         let node_ref = node.borrow();
         let mut table_ref = node_ref.symbol_table.borrow_mut();
@@ -277,8 +364,12 @@ impl Visitor for SymbolTableVisitor {
                     let mut id_str: Vec<String> = vec![];
 
                     for child in id.children() {
-                        if let NodeValue::Leaf(Type::Id(id)) = &child.borrow().value {
-                            id_str.push(id.clone());
+                        match &child.borrow().value {
+                            NodeValue::Leaf(Type::Id(id)) => id_str.push(id.clone()),
+                            NodeValue::Leaf(Type::Constructor) => {
+                                id_str.push(String::from("constructor"))
+                            }
+                            _ => (),
                         }
                     }
 
@@ -302,53 +393,54 @@ impl Visitor for SymbolTableVisitor {
         };
 
         let mut fmt_params: Vec<Rc<RefCell<SymbolData>>> = vec![];
-        let mut size = 0;
 
         if let Some(param_table) = param_list.borrow().symbol_table.borrow().clone() {
-            for (_, value) in param_table.iter() {
-                fmt_params.push(value.clone());
-                size += value.borrow().size;
-            }
+            fmt_params.extend(param_table.values().cloned());
             table.extend(param_table);
         }
 
-        if let Some(t) = return_type {
-            let return_size = get_type_size(&t);
-
-            table.insert(
-                String::from("_return"),
-                Rc::new(RefCell::new(SymbolData::new(
-                    return_size,
-                    0,
-                    match t {
-                        Type::Integer => VarType::Integer(vec![]),
-                        Type::Float => VarType::Float(vec![]),
-                        Type::Id(id) => VarType::Class(id),
-                        Type::Void => VarType::Void,
-                        _ => {
-                            return Err(CompilerError::new(
-                                format!("Expected type at '{}'!", node.borrow().value),
-                                node.borrow().token.clone(),
-                            )
-                            .into())
-                        }
-                    },
-                ))),
-            );
+        // Constructors don't return anything
+        table.return_type = Some(match return_type.unwrap_or(Type::Void) {
+            Type::Integer => VarType::Integer(vec![]),
+            Type::Float => VarType::Float(vec![]),
+            Type::Id(id) => VarType::Class(id, vec![]),
+            Type::Void => VarType::Void,
+            _ => {
+                return Err(CompilerError::new(
+                    format!("Expected type at '{}'!", node.borrow().value),
+                    node.borrow().token.clone(),
+                )
+                .into())
+            }
+        });
+
+        // Member functions get a hidden pointer to their object, right after the parameters
+        if let NodeValue::Tree(TreeNode::Scope()) = &id.borrow().value {
+            if let Some(class_name) = func_name.split("::").next() {
+                let offset = -self.offset.fetch_add(INT_SIZE as isize, Ordering::SeqCst);
+                let self_data = Rc::new(RefCell::new(SymbolData::new(
+                    SymbolKind::Parameter,
+                    INT_SIZE,
+                    offset,
+                    VarType::Class(class_name.to_string(), vec![]),
+                )));
+
+                table.insert("self".to_string(), self_data.clone());
+                self.current_table
+                    .borrow_mut()
+                    .insert("self".to_string(), self_data);
+            }
         }
 
-        fmt_params.sort_by(|a, b| b.borrow().offset.cmp(&a.borrow().offset));
+        fmt_params.sort_by_key(|p| std::cmp::Reverse(p.borrow().offset));
         let fmt_params = fmt_params
             .iter()
             .map(|s| s.borrow().var_type.to_string())
             .collect::<Vec<_>>()
             .join(", ");
         let func_signature = format!("{func_name}({fmt_params})");
-
-        table.insert(
-            func_signature.clone(),
-            Rc::new(RefCell::new(SymbolData::new(size, 0, VarType::Function))),
-        );
+        table.scope = ScopeKind::Function(func_signature.clone());
+        self.current_table.borrow_mut().scope = ScopeKind::Function(func_signature.clone());
 
         let func_label = if func_signature == "main()" {
             String::from("main")
@@ -382,6 +474,24 @@ impl Visitor for SymbolTableVisitor {
         Ok(())
     }
 
+    fn visit_misused_self(&mut self, _node: &CodeNode, self_leaf: &CodeNode) -> VisitorResult {
+        // Only the target of an assignment or a read can be a bare `self` variable
+        let assigned = self_leaf.parent().is_some_and(|parent| {
+            matches!(parent.borrow().value, NodeValue::Tree(TreeNode::Variable()))
+        });
+        let message = if assigned {
+            "Cannot assign to 'self', it always refers to the object of a member function!"
+        } else {
+            "'self' can't be used as a name, it only refers to the object of a member function!"
+        };
+
+        Err(
+            CompilerError::new(message.to_string(), self_leaf.borrow().token.clone())
+                .with_code("E0214")
+                .into(),
+        )
+    }
+
     fn visit_variable(
         &mut self,
         node: &CodeNode,
@@ -393,6 +503,8 @@ impl Visitor for SymbolTableVisitor {
 
         // These next two checks should *never* fail, ideally
         let id = match id {
+            // The hidden parameter of member functions
+            Type::SelfT => String::from("self"),
             Type::Id(id) => id,
             _ => {
                 return Err(CompilerError::new(
@@ -403,17 +515,43 @@ impl Visitor for SymbolTableVisitor {
             }
         };
 
-        let func_table = self.current_table.borrow();
+        let current_table = self.current_table.clone();
+        let func_table = current_table.borrow();
 
         let var_data = match func_table.get(&id) {
-            Some(var_data) => var_data,
-            None => {
-                return Err(CompilerError::new(
-                    format!("Unknown variable '{id}'!"),
-                    node_ref.token.clone(),
+            Some(var_data) => {
+                // `self` isn't declared anywhere
+                if id != "self" {
+                    self.add_reference(node, SymbolId::new(func_table.scope.clone(), &id));
+                }
+
+                var_data.clone()
+            }
+            None if id == "self" => {
+                poison(node);
+                return Err(CompilerError::new_with_node(
+                    "'self' can only be used inside member functions!".to_string(),
+                    &node_ref,
                 )
-                .into())
+                .with_code("E0207")
+                .into());
             }
+            // Attributes can be used by name in member functions, like `self.x`
+            None => match resolve_attribute(node, &id).inspect_err(|_| poison(node))? {
+                Some((owner, attribute_data)) => {
+                    self.add_reference(node, SymbolId::new(ScopeKind::Class(owner), &id));
+                    attribute_data
+                }
+                None => {
+                    poison(node);
+                    return Err(CompilerError::new_with_node(
+                        format!("Unknown variable '{id}'!"),
+                        &node_ref,
+                    )
+                    .with_code("E0201")
+                    .into());
+                }
+            },
         };
 
         let var_type = var_data.borrow().var_type.clone();
@@ -421,60 +559,72 @@ impl Visitor for SymbolTableVisitor {
         // Set it even before checking so we can get more errors later (and not panic)
         node_ref.var_type.borrow_mut().replace(var_type.clone());
 
-        let check_dimensions = |dimensions: usize, indexes: &Vec<usize>| -> VisitorResult {
-            if dimensions > indexes.len() {
+        if let Some(idx) = indices {
+            let new_type = index_type(&id, &var_type, &idx).inspect_err(|_| poison(node))?;
+            node_ref.var_type.borrow_mut().replace(new_type);
+            check_bounds(&id, &var_type, &idx)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_nested_var(
+        &mut self,
+        node: &CodeNode,
+        object: CodeNode,
+        id: Type,
+        indices: CodeNode,
+    ) -> VisitorResult {
+        let node_ref = node.borrow();
+
+        let member = match id {
+            Type::Id(id) => id,
+            _ => {
                 return Err(CompilerError::new(
-                    format!(
-                        "Expected max {} dimensions for array '{id}', got {dimensions}!",
-                        indexes.len(),
-                    ),
+                    format!("Expected identifier at '{}'!", node_ref.value),
                     node_ref.token.clone(),
                 )
-                .into());
+                .into())
             }
-
-            Ok(())
         };
 
-        // Check if our variable is indexed
-        // If it is, then we have to "resolve" the type using the indices
-        // Basically, if we have VarType::Integer([7, 8, 9]) representing arr: integer[7][8][9]
-        // then arr[1]    -> VarType::Integer([8, 9])
-        // and  arr[1][2] -> VarType::Integer([9])
-        // "popping off" dimensions from the start
-        if let Some(idx) = indices {
-            if idx.children().count() > 0 {
-                let new_type = match *idx.borrow().var_type.borrow() {
-                    Some(VarType::IndiceList(dimensions)) => match var_type {
-                        VarType::Integer(indexes) => {
-                            check_dimensions(dimensions, &indexes)?;
-                            VarType::Integer(indexes[dimensions..].to_vec())
-                        }
-                        VarType::Float(indexes) => {
-                            check_dimensions(dimensions, &indexes)?;
-                            VarType::Float(indexes[dimensions..].to_vec())
-                        }
-                        _ => {
-                            return Err(CompilerError::new(
-                                format!("Indexed a non-integer or non-float array at '{id}'!"),
-                                idx.borrow().token.clone(),
-                            )
-                            .into());
-                        }
-                    },
-                    _ => {
-                        return Err(CompilerError::new(
-                            format!("Expected indice list for array '{id}'!"),
-                            idx.borrow().token.clone(),
-                        )
-                        .into());
-                    }
-                };
+        // Whatever we're accessing already failed to check, that error is enough
+        let (owner, member_data) =
+            match resolve_member(node, &object, &member).inspect_err(|_| poison(node))? {
+                Some(resolved) => resolved,
+                None => {
+                    poison(node);
+                    return Ok(());
+                }
+            };
 
-                node_ref.var_type.borrow_mut().replace(new_type);
-            }
+        self.add_reference(
+            node,
+            SymbolId::new(ScopeKind::Class(owner.clone()), &member),
+        );
+
+        let member_ref = member_data.borrow();
+
+        if member_ref.var_type == VarType::Function {
+            poison(node);
+            return Err(CompilerError::new_with_node(
+                format!("'{owner}::{member}' is a member function, not an attribute!"),
+                &node_ref,
+            )
+            .with_code("E0210")
+            .into());
         }
 
+        node_ref
+            .var_type
+            .borrow_mut()
+            .replace(member_ref.var_type.clone());
+
+        let new_type =
+            index_type(&member, &member_ref.var_type, &indices).inspect_err(|_| poison(node))?;
+        node_ref.var_type.borrow_mut().replace(new_type);
+        check_bounds(&member, &member_ref.var_type, &indices)?;
+
         Ok(())
     }
 
@@ -493,20 +643,29 @@ impl Visitor for SymbolTableVisitor {
         for index in indices {
             let index_ref = index.borrow();
             let index_type = index_ref.var_type.borrow().clone().unwrap_or_else(|| {
-                if let NodeValue::Leaf(Type::IntNum(_)) = index_ref.value {
+                // Unsized dimensions of array parameters (`integer[]`) aren't indices
+                if let NodeValue::Leaf(Type::IntNum(_) | Type::CloseSqbr) = index_ref.value {
                     VarType::Integer(vec![])
                 } else {
                     VarType::Void
                 }
             });
 
-            // *Only* integers, not arrays or anything else
-            if index_type != VarType::Integer(vec![]) {
-                return Err(CompilerError::new(
-                    format!("Array indices must be integers ({index} is not)!"),
-                    node_ref.token.clone(),
-                )
-                .into());
+            // *Only* integers, not arrays or anything else (indices that failed to check already
+            // reported why)
+            if index_type != VarType::Integer(vec![]) && index_type != VarType::Error {
+                // Float literals (and arithmetic on them) can at least say which value it is
+                let message = match evaluate(&index) {
+                    // Debug keeps the `.0` of whole floats, `1.0` isn't `1`
+                    Some(Constant::Float(value)) => {
+                        format!("Array indices must be integers ({value:?} is a float)!")
+                    }
+                    _ => format!("Array indices must be integers ({index} is not)!"),
+                };
+
+                return Err(CompilerError::new_with_node(message, &index_ref)
+                    .with_code("E0305")
+                    .into());
             }
         }
 
@@ -532,32 +691,15 @@ impl Visitor for SymbolTableVisitor {
         };
 
         let size: usize;
-        let var_type: VarType;
+        let mut var_type: VarType;
 
         let var_key = match &indice_or_args.borrow().value {
             NodeValue::Tree(t) => match t {
                 TreeNode::IndiceList() => {
-                    let indices = indice_or_args
-                        .children()
-                        .map(|num| -> Result<usize, CompilerError> {
-                            if let NodeValue::Leaf(Type::IntNum(n)) = &num.borrow().value {
-                                usize::try_from(*n).map_err(|_| {
-                                    CompilerError::new(
-                                        "Expected usize!".to_string(),
-                                        num.borrow().token.clone(),
-                                    )
-                                })
-                            } else {
-                                Err(CompilerError::new(
-                                    "Expected number!".to_string(),
-                                    num.borrow().token.clone(),
-                                ))
-                            }
-                        })
-                        .collect::<Result<Vec<_>, _>>()?;
+                    let indices = get_declared_dimensions(&indice_or_args, false)?;
 
                     let indice_mult: usize = indices.iter().product();
-                    size = get_type_size(&type_) * indice_mult.max(1);
+                    size = get_var_size(node, &type_) * indice_mult.max(1);
 
                     var_type = if type_ == Type::Integer {
                         VarType::Integer(indices)
@@ -565,7 +707,7 @@ impl Visitor for SymbolTableVisitor {
                         VarType::Float(indices)
                     } else {
                         match type_ {
-                            Type::Id(class_name) => VarType::Class(class_name),
+                            Type::Id(class_name) => VarType::Class(class_name, indices),
                             _ => {
                                 return Err(CompilerError::new(
                                     format!("Expected class identifier at '{}'!", var_name),
@@ -579,10 +721,10 @@ impl Visitor for SymbolTableVisitor {
                     var_name
                 }
                 TreeNode::ArgumentList() => {
-                    size = 0;
+                    size = get_var_size(node, &type_);
 
                     if let Type::Id(_id) = &type_ {
-                        var_type = VarType::Class(_id.clone());
+                        var_type = VarType::Class(_id.clone(), vec![]);
                         var_name
                     } else {
                         return Err(CompilerError::new(
@@ -612,45 +754,56 @@ impl Visitor for SymbolTableVisitor {
             }
         };
 
-        if let VarType::Class(class_name) = &var_type {
-            let global_table = get_global_table(node)?;
+        let mut errors: Vec<CompilerError> = vec![];
 
-            if !global_table.contains_key(class_name) {
-                return Err(CompilerError::new(
-                    format!("Class '{class_name}' not found!"),
-                    node.borrow().token.clone(),
-                )
-                .into());
+        if let VarType::Class(class_name, _) = &var_type {
+            if !with_global_table(node, |global| global.contains_key(class_name))? {
+                errors.push(
+                    CompilerError::new(
+                        format!("Class '{class_name}' not found!"),
+                        node.borrow().token.clone(),
+                    )
+                    .with_code("E0202"),
+                );
+
+                // Still declare it, so its uses aren't unknown variables
+                var_type = VarType::Error;
             }
         }
 
         let offset = -self.offset.fetch_add(size as isize, Ordering::SeqCst);
 
+        let mut symbol_data = SymbolData::new(SymbolKind::Local, size, offset, var_type.clone());
+        symbol_data.location = get_declared_location(node);
+
         let node_ref = node.borrow();
         let mut table_ref = node_ref.symbol_table.borrow_mut();
         let table = table_ref.get_or_insert_with(Default::default);
-        table.insert(
-            var_key.clone(),
-            Rc::new(RefCell::new(SymbolData::new(
-                size,
-                offset,
-                var_type.clone(),
-            ))),
-        );
+        table.insert(var_key.clone(), Rc::new(RefCell::new(symbol_data.clone())));
 
-        let mut func_table = self.current_table.borrow_mut();
-        func_table.insert(
-            var_key,
-            Rc::new(RefCell::new(SymbolData::new(
-                size,
-                offset,
-                var_type.clone(),
-            ))),
+        self.current_table
+            .borrow_mut()
+            .insert(var_key, Rc::new(RefCell::new(symbol_data)));
+
+        node_ref.var_type.borrow_mut().replace(var_type.clone());
+
+        // Objects declared with arguments are built by one of their class' constructors
+        let is_constructed = matches!(
+            indice_or_args.borrow().value,
+            NodeValue::Tree(TreeNode::ArgumentList())
         );
 
-        node_ref.var_type.borrow_mut().replace(var_type);
+        if let (VarType::Class(class_name, _), true) = (&var_type, is_constructed) {
+            with_global_table(node, |global| {
+                self.check_constructor_call(node, global, class_name, &indice_or_args)
+            })??;
+        }
 
-        Ok(())
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
     }
 
     fn visit_assignment(
@@ -659,108 +812,46 @@ impl Visitor for SymbolTableVisitor {
         variable: CodeNode,
         expr: CodeNode,
     ) -> VisitorResult {
-        let var_type = variable.borrow().var_type.borrow().clone().ok_or_else(|| {
-            CompilerError::new(
-                "Variable has no type!".to_string(),
-                node.borrow().token.clone(),
-            )
-        })?;
-        let expr_type = expr.borrow().var_type.borrow().clone();
+        let (var_type, expr_type) = match (operand_type(&variable), operand_type(&expr)) {
+            (Some(var_type), Some(expr_type)) => (var_type, expr_type),
+            _ => return Ok(()),
+        };
 
-        if let Some(expr_type) = expr_type {
-            if var_type != expr_type {
-                return Err(CompilerError::new(
-                    format!(
-                        "Cannot assign expression of type '{:?}' to variable of type '{:?}'!",
-                        expr_type, var_type
-                    ),
-                    node.borrow().token.clone(),
-                )
-                .into());
-            };
-        } else {
-            return Err(CompilerError::new(
-                "Expression has no type!".to_string(),
-                node.borrow().token.clone(),
+        if var_type != expr_type {
+            return Err(CompilerError::new_with_node(
+                format!(
+                    "Cannot assign expression of type '{expr_type}' to variable of type '{var_type}'!"
+                ),
+                &node.borrow(),
             )
+            .with_code("E0301")
             .into());
-        }
+        };
 
         Ok(())
     }
 
     fn visit_expr(&mut self, node: &CodeNode, expr: Vec<CodeNode>) -> VisitorResult {
-        if expr.is_empty() {
-            return Err(CompilerError::new(
-                "Empty expression?".to_string(),
-                node.borrow().token.clone(),
-            )
-            .into());
-        } else if expr.len() == 1 {
-            let node_ref = node.borrow();
-            let first = expr.first().unwrap().borrow();
-
-            let label = first.label.borrow().clone();
-            *node_ref.label.borrow_mut() = label;
-
-            let code = first.code.borrow().clone();
-            *node_ref.code.borrow_mut() = code;
-
-            let var_type = first.var_type.borrow().clone();
-            *node_ref.var_type.borrow_mut() = var_type;
-        } else {
-            // Dot-indexed expression
-            let node_ref = node.borrow();
-            let first = expr.first().unwrap().borrow();
-            let var_borrow = first.var_type.borrow();
-
-            let label = first.label.borrow().clone();
-            *node_ref.label.borrow_mut() = label;
-
-            let code = first.code.borrow().clone();
-            *node_ref.code.borrow_mut() = code;
-
-            let var_type = first.var_type.borrow().clone();
-            *node_ref.var_type.borrow_mut() = var_type;
-
-            if let Some(VarType::Class(class_name)) = var_borrow.clone() {
-                let global_table = get_global_table(node)?;
-                if let Some(class) = global_table.get(&class_name) {
-                    let class_table = class.borrow().table.clone().unwrap();
-                    let indexed_by = expr.get(1).unwrap();
-
-                    if let NodeValue::Leaf(Type::Id(id)) = indexed_by.borrow().value.clone() {
-                        if let Some(data) = class_table.get(&id) {
-                            // TODO: code for offset loading
-                            let data_ref = data.borrow();
-                            if data_ref.visibility != Some(Visibility::Public) {
-                                return Err(CompilerError::new(
-                                    format!(
-                                        "Cannot access private member '{id}' of class '{class_name}'!",
-                                        id = id,
-                                        class_name = class_name
-                                    ),
-                                    node_ref.token.clone(),
-                                )
-                                .into());
-                            }
-                        } else {
-                            return Err(CompilerError::new(
-                                format!("Class '{class_name}' has no member '{id}'!",),
-                                node_ref.token.clone(),
-                            )
-                            .into());
-                        }
-                    };
-                };
-            } else {
+        let node_ref = node.borrow();
+        let first = match expr.first() {
+            Some(first) => first.borrow(),
+            None => {
                 return Err(CompilerError::new(
-                    "Dot operator used on non-class type!".to_string(),
+                    "Empty expression?".to_string(),
                     node_ref.token.clone(),
                 )
-                .into());
+                .into())
             }
-        }
+        };
+
+        let label = first.label.borrow().clone();
+        *node_ref.label.borrow_mut() = label;
+
+        let code = first.code.borrow().clone();
+        *node_ref.code.borrow_mut() = code;
+
+        let var_type = first.var_type.borrow().clone();
+        *node_ref.var_type.borrow_mut() = var_type;
 
         Ok(())
     }
@@ -769,75 +860,162 @@ impl Visitor for SymbolTableVisitor {
         &mut self,
         node: &CodeNode,
         left: CodeNode,
-        _op: CodeNode,
+        op: CodeNode,
         right: CodeNode,
     ) -> VisitorResult {
-        let node_ref = node.borrow();
-        let left_ref = left.borrow();
-        let right_ref = right.borrow();
+        let (left_type, right_type) = match (operand_type(&left), operand_type(&right)) {
+            (Some(left_type), Some(right_type)) => (left_type, right_type),
+            _ => {
+                poison(node);
+                return Ok(());
+            }
+        };
 
-        let left_type = left_ref.var_type.borrow().clone().ok_or_else(|| {
-            CompilerError::new(
-                "Failed to get type of left side of expression".to_string(),
-                left_ref.token.clone(),
-            )
-        })?;
-        let right_type = right_ref.var_type.borrow().clone().ok_or_else(|| {
-            CompilerError::new(
-                "Failed to get type of right side of expression".to_string(),
-                right_ref.token.clone(),
-            )
-        })?;
+        node.borrow()
+            .var_type
+            .borrow_mut()
+            .replace(left_type.clone());
 
-        // Just set this already before checking, so in case of errors we're propagating *something*
-        // Any messed up values will just cause more errors later lol
-        node_ref.var_type.borrow_mut().replace(left_type.clone());
+        let op_ref = op.borrow();
+        let lexeme = &op_ref.token.lexeme;
 
-        // check if left/right type have any indexes (error if they do, since we can't add arrays)
-        let check_type = |vt: &VarType| -> VisitorResult {
-            match vt {
-                VarType::Integer(indexes) | VarType::Float(indexes) => {
-                    if !indexes.is_empty() {
-                        return Err(CompilerError::new(
-                            "Cannot do arithmetic on arrays!".into(),
-                            node_ref.token.clone(),
-                        )
-                        .into());
-                    }
-                }
-                _ => {
-                    return Err(CompilerError::new(
-                        "You can only do arithmetic on integers or floats!".into(),
-                        node_ref.token.clone(),
-                    )
-                    .into())
-                }
+        // `and`/`or` are logical, so they only work on integers (which are the booleans)
+        let allowed = match op_ref.value {
+            NodeValue::Leaf(Type::And | Type::Or) => INTEGER_OPERANDS,
+            _ => NUMBER_OPERANDS,
+        };
+
+        check_operand(node, lexeme, &left_type, allowed)
+            .and_then(|_| check_operand(node, lexeme, &right_type, allowed))
+            .and_then(|_| check_operands_match(node, lexeme, &left_type, &right_type))
+            .inspect_err(|_| poison(node))?;
+
+        check_division(node, &op_ref.value, &right)
+    }
+
+    fn visit_rel_expr(
+        &mut self,
+        node: &CodeNode,
+        left: CodeNode,
+        _op: Type,
+        right: CodeNode,
+    ) -> VisitorResult {
+        // Comparisons are true or false, so 1 or 0, whether the operands check or not
+        node.borrow()
+            .var_type
+            .borrow_mut()
+            .replace(VarType::Integer(vec![]));
+
+        let (left_type, right_type) = match (operand_type(&left), operand_type(&right)) {
+            (Some(left_type), Some(right_type)) => (left_type, right_type),
+            _ => return Ok(()),
+        };
+
+        let lexeme = left
+            .next_sibling()
+            .map(|op| op.borrow().token.lexeme.clone())
+            .unwrap_or_default();
+
+        check_operand(node, &lexeme, &left_type, NUMBER_OPERANDS)?;
+        check_operand(node, &lexeme, &right_type, NUMBER_OPERANDS)?;
+        check_operands_match(node, &lexeme, &left_type, &right_type)
+    }
+
+    fn visit_unary_factor(&mut self, node: &CodeNode, op: Type, factor: CodeNode) -> VisitorResult {
+        let factor_type = match operand_type(&factor) {
+            Some(factor_type) => factor_type,
+            None => {
+                poison(node);
+                return Ok(());
             }
+        };
 
-            Ok(())
+        let (allowed, var_type) = match op {
+            Type::Not => (INTEGER_OPERANDS, VarType::Integer(vec![])),
+            _ => (NUMBER_OPERANDS, factor_type.clone()),
         };
 
-        check_type(&left_type)?;
-        check_type(&right_type)?;
+        node.borrow().var_type.borrow_mut().replace(var_type);
 
-        if !left_type.eq_variant(&right_type) {
-            return Err(CompilerError::new(
-                format!("Cannot do arithmetic on mixed types (Tried {left_type} & {right_type})!",),
-                node_ref.token.clone(),
-            )
-            .into());
-        }
+        let lexeme = node
+            .first_child()
+            .map(|op| op.borrow().token.lexeme.clone())
+            .unwrap_or_default();
+
+        check_operand(node, &lexeme, &factor_type, allowed).inspect_err(|_| poison(node))
+    }
+
+    fn visit_paren_factor(&mut self, node: &CodeNode, expr: CodeNode) -> VisitorResult {
+        let var_type = expr.borrow().var_type.borrow().clone();
+        *node.borrow().var_type.borrow_mut() = var_type;
 
         Ok(())
     }
 
+    fn visit_if(
+        &mut self,
+        _node: &CodeNode,
+        condition: CodeNode,
+        _if_block: CodeNode,
+        _else_block: CodeNode,
+    ) -> VisitorResult {
+        check_condition(&condition)
+    }
+
+    fn visit_while(
+        &mut self,
+        _node: &CodeNode,
+        condition: CodeNode,
+        _while_block: CodeNode,
+    ) -> VisitorResult {
+        check_condition(&condition)
+    }
+
+    fn visit_write(&mut self, node: &CodeNode, expr: CodeNode) -> VisitorResult {
+        match operand_type(&expr) {
+            Some(expr_type) => check_operand(node, "write", &expr_type, NUMBER_OPERANDS),
+            None => Ok(()),
+        }
+    }
+
+    fn visit_return(&mut self, node: &CodeNode, expr: CodeNode) -> VisitorResult {
+        let expr_type = match operand_type(&expr) {
+            Some(expr_type) => expr_type,
+            None => return Ok(()),
+        };
+
+        // The head has the declared return type, the function table isn't filled in yet
+        let return_type = get_current_function(node)
+            .and_then(|f| f.first_child())
+            .and_then(|head| {
+                head.borrow()
+                    .symbol_table
+                    .borrow()
+                    .as_ref()?
+                    .return_type
+                    .clone()
+            });
+
+        match return_type {
+            // Returning from a void function is a control flow error
+            Some(VarType::Void) => Ok(()),
+            Some(return_type) if return_type != expr_type => Err(CompilerError::new_with_node(
+                format!("Return type mismatch, expected '{return_type}' but found '{expr_type}'!"),
+                &expr.borrow(),
+            )
+            .with_code("E0304")
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
     fn visit_function_call(
         &mut self,
         node: &CodeNode,
+        object: Option<CodeNode>,
         id: Type,
         param_list: CodeNode,
     ) -> VisitorResult {
-        let node_ref = node.borrow();
         let func_name = match id {
             Type::Id(id) => id,
             _ => {
@@ -849,92 +1027,344 @@ impl Visitor for SymbolTableVisitor {
             }
         };
 
-        let param_types = param_list
+        let args = param_list
             .children()
-            .map(|c| {
-                let c_ref = c.borrow();
-                let c_type = c_ref.var_type.borrow().clone();
-                c_type.map_or_else(|| String::from("?"), |v| v.to_string())
-            })
-            .collect::<Vec<_>>()
-            .join(", ");
+            .map(|c| c.borrow().var_type.borrow().clone())
+            .collect::<Vec<_>>();
 
-        let func_signature = format!("{}({})", func_name, param_types);
-        let global_table = get_global_table(node)?;
-
-        let func_data = global_table.get(&func_signature).ok_or_else(|| {
-            node_ref
-                .code
-                .borrow_mut()
-                .replace("addi r13, r0 , 0".to_string());
-            node_ref.label.borrow_mut().replace("r13".to_string());
-            CompilerError::new(
-                format!("Function '{}' not found!", func_signature),
-                node_ref.token.clone(),
+        with_global_table(node, |global| {
+            self.check_call(node, global, object, func_name, args)
+        })?
+    }
+}
+
+/// Find `member` in the class of `object` (or the classes it inherits), along with the class that
+/// declares it
+///
+/// Returns `None` if `object` is poisoned, since whatever made it fail already reported an error
+fn resolve_member(
+    node: &CodeNode,
+    object: &CodeNode,
+    member: &str,
+) -> CompilerResult<Option<(String, Rc<RefCell<SymbolData>>)>> {
+    let object_type = match operand_type(object) {
+        Some(object_type) => object_type,
+        None => return Ok(None),
+    };
+
+    let class_name = match object_type {
+        VarType::Class(class_name, dims) if dims.is_empty() => class_name,
+        VarType::Class(_, _) => {
+            return Err(CompilerError::new_with_node(
+                format!("Cannot access member '{member}' of an array of objects, index it first!"),
+                &node.borrow(),
             )
-        })?;
+            .with_code("E0213")
+            .into())
+        }
+        _ => {
+            return Err(CompilerError::new_with_node(
+                format!("Dot operator used on non-class type '{object_type}'!"),
+                &node.borrow(),
+            )
+            .with_code("E0212")
+            .into())
+        }
+    };
+
+    let (owner, member_data) =
+        with_global_table(node, |global| get_class_member(global, &class_name, member))?
+            .ok_or_else(|| {
+                CompilerError::new_with_node(
+                    format!("Class '{class_name}' has no member '{member}'!"),
+                    &node.borrow(),
+                )
+                .with_code("E0206")
+            })?;
 
-        let func_table = func_data.borrow();
-        let (_, return_type) = func_table
-            .table
-            .as_ref()
-            .unwrap()
-            .iter()
-            .find(|(k, _)| k == &"_return")
-            .ok_or(CompilerError::new(
-                "Function return type not found in head table".to_string(),
-                node_ref.token.clone(),
-            ))?;
+    // Calls check the overload they resolve to instead
+    if member_data.borrow().kind == SymbolKind::Attribute {
+        check_access(node, &owner, member, &member_data)?;
+    }
 
-        let return_type = return_type.borrow().clone().var_type;
-        node_ref.var_type.borrow_mut().replace(return_type);
+    Ok(Some((owner, member_data)))
+}
 
-        Ok(())
+/// Resolve a name used on its own in a member function to an attribute of its class (or a class it
+/// inherits from), if it is one
+fn resolve_attribute(
+    node: &CodeNode,
+    id: &str,
+) -> CompilerResult<Option<(String, Rc<RefCell<SymbolData>>)>> {
+    let class_name = match get_current_class(node) {
+        Some(class_name) => class_name,
+        None => return Ok(None),
+    };
+
+    let resolved = with_global_table(node, |global| get_class_member(global, &class_name, id))?;
+
+    let (owner, member_data) = match resolved {
+        Some(resolved) => resolved,
+        None => return Ok(None),
+    };
+
+    if member_data.borrow().kind != SymbolKind::Attribute {
+        return Err(CompilerError::new_with_node(
+            format!("'{owner}::{id}' is a member function, not an attribute!"),
+            &node.borrow(),
+        )
+        .with_code("E0210")
+        .into());
     }
+
+    check_access(node, &owner, id, &member_data)?;
+
+    Ok(Some((owner, member_data)))
 }
 
-pub fn get_type_size(type_: &Type) -> usize {
-    if type_.eq_variant(&Type::IntNum(0)) || type_.eq(&Type::Integer) {
-        INT_SIZE
-    } else if type_.eq_variant(&Type::FloatNum(0.0)) || type_.eq(&Type::Float) {
-        FLOAT_SIZE
-    } else {
-        0 // TODO: handle classes?
+/// Resolve the overload a call (or constructed object) at `node` targets, see `resolve_overload`
+///
+/// Returns `None` if it's only ambiguous because some arguments failed to check, since any of the
+/// overloads could be the right one.
+fn resolve_call(
+    global_table: &SymbolTable,
+    name: &str,
+    args: &[Option<VarType>],
+    node: &StructNode,
+) -> CompilerResult<Option<String>> {
+    match resolve_overload(global_table, name, args) {
+        Ok(target) => Ok(Some(target)),
+        Err(e) if e.code == "E0205" && args.iter().any(is_poisoned) => Ok(None),
+        Err(e) => Err(e.into_error(node).into()),
+    }
+}
+
+/// Dividing by a constant zero is never what was meant, MOON can't compute it either
+fn check_division(node: &CodeNode, op: &NodeValue, divisor: &CodeNode) -> VisitorResult {
+    let is_zero = matches!(
+        evaluate(divisor),
+        Some(Constant::Integer(0)) | Some(Constant::Float(0.0))
+    );
+
+    if matches!(op, NodeValue::Leaf(Type::Div)) && is_zero {
+        return Err(CompilerError::new_with_node(
+            String::from("Division by zero!"),
+            &node.borrow(),
+        )
+        .with_code("E0309")
+        .into());
     }
+
+    Ok(())
 }
 
-pub fn get_expr_type(node: &CodeNode) -> Result<Type, String> {
-    match &node.borrow().value {
-        // TODO: handle ID
-        NodeValue::Leaf(t) => Ok(t.clone()),
-        NodeValue::Marker => Err(format!("Unexpected marker at '{}'!", node.borrow().value)),
-        NodeValue::Tree(_) => {
-            let mut type_: Option<Type> = None;
+/// Private members are only accessible from the member functions of the class that declares them
+///
+/// That includes inherited members: if `B isa A`, then `B`'s member functions can't use the private
+/// members of `A` either, even on a `B`. They can only go through `A`'s public members.
+fn check_access(
+    node: &CodeNode,
+    owner: &str,
+    member: &str,
+    member_data: &Rc<RefCell<SymbolData>>,
+) -> VisitorResult {
+    if member_data.borrow().visibility != Some(Visibility::Private)
+        || get_current_class(node).as_deref() == Some(owner)
+    {
+        return Ok(());
+    }
 
-            let children = node.children();
+    let access_site = get_current_function_name(node).unwrap_or_else(|| String::from("?"));
 
-            for child in children {
-                let child_type = get_expr_type(&child)?;
+    Err(CompilerError::new_with_node(
+        format!(
+            "Cannot access private member '{owner}::{member}' from '{access_site}', it's only accessible inside class '{owner}'!"
+        ),
+        &node.borrow(),
+    )
+    .with_code("E0209")
+    .into())
+}
 
-                if !child_type.is_literal() {
-                    // ignore mult/plus/etc types
-                    continue;
-                }
+/// What operators on numbers accept, either type works
+const NUMBER_OPERANDS: &[&str] = &["Integer", "Float"];
+/// What logical operators accept, booleans are integers
+const INTEGER_OPERANDS: &[&str] = &["Integer"];
+
+/// The type of an operand that was already visited, `None` if it's poisoned
+///
+/// Poisoned operands failed to check and already reported why, so there's nothing left to check
+/// for whatever uses them.
+fn operand_type(operand: &CodeNode) -> Option<VarType> {
+    let var_type = operand.borrow().var_type.borrow().clone();
+    var_type.filter(|t| *t != VarType::Error)
+}
 
-                if let Some(old_type) = type_ {
-                    if old_type != child_type {
-                        return Err(format!(
-                            "Expected type '{:?}' at '{}'!",
-                            old_type,
-                            node.borrow().value
-                        ));
-                    }
-                }
+/// Whether a type is missing or an `Error`, see `poison`
+fn is_poisoned(var_type: &Option<VarType>) -> bool {
+    matches!(var_type, None | Some(VarType::Error))
+}
 
-                type_ = Some(child_type);
-            }
+/// Give `node` the `Error` type, because it (or something it's made of) failed to check
+///
+/// Whatever uses it then skips its own checks, so each mistake is only reported once.
+fn poison(node: &CodeNode) {
+    node.borrow().var_type.borrow_mut().replace(VarType::Error);
+}
 
-            type_.ok_or_else(|| format!("Found no expr type at '{}'!", node.borrow().value))
+/// Check `op` can be used on a `var_type`, which has to be a single value of one of `allowed`
+fn check_operand(node: &CodeNode, op: &str, var_type: &VarType, allowed: &[&str]) -> VisitorResult {
+    let base_type = match var_type {
+        VarType::Integer(_) => "Integer",
+        VarType::Float(_) => "Float",
+        _ => "",
+    };
+
+    if allowed.contains(&base_type) && var_type.dimensions().is_empty() {
+        return Ok(());
+    }
+
+    let expected = allowed
+        .iter()
+        .map(|a| format!("'{a}'"))
+        .collect::<Vec<_>>()
+        .join(" or ");
+
+    Err(CompilerError::new_with_node(
+        format!("'{op}' expects {expected}, but found '{var_type}'!"),
+        &node.borrow(),
+    )
+    .with_code("E0303")
+    .into())
+}
+
+/// Both sides of a binary operator need the same type, there are no implicit conversions
+fn check_operands_match(
+    node: &CodeNode,
+    op: &str,
+    left: &VarType,
+    right: &VarType,
+) -> VisitorResult {
+    if left == right {
+        return Ok(());
+    }
+
+    Err(CompilerError::new_with_node(
+        format!("Mismatched types for '{op}', expected '{left}' but found '{right}'!"),
+        &node.borrow(),
+    )
+    .with_code("E0302")
+    .into())
+}
+
+/// `if` and `while` conditions are booleans, so integers
+///
+/// The grammar only allows relational expressions there, so this is just a sanity check.
+fn check_condition(condition: &CodeNode) -> VisitorResult {
+    let condition_type = match operand_type(condition) {
+        Some(VarType::Integer(dims)) if dims.is_empty() => return Ok(()),
+        Some(condition_type) => condition_type,
+        None => return Ok(()),
+    };
+
+    Err(CompilerError::new_with_node(
+        format!("Condition expects 'Integer', but found '{condition_type}'!"),
+        &condition.borrow(),
+    )
+    .into())
+}
+
+/// Resolve the type of `name[indices]`, given `name` is a `var_type`
+fn index_type(name: &str, var_type: &VarType, indices: &CodeNode) -> CompilerResult<VarType> {
+    let dimensions = match *indices.borrow().var_type.borrow() {
+        Some(VarType::IndiceList(dimensions)) => dimensions,
+        _ => {
+            return Err(CompilerError::new(
+                format!("Expected indice list for array '{name}'!"),
+                indices.borrow().token.clone(),
+            )
+            .into());
         }
+    };
+
+    var_type.indexed(dimensions).ok_or_else(|| {
+        CompilerError::new_with_node(
+            format!(
+                "Expected max {} dimensions for array '{name}', got {dimensions}!",
+                var_type.dimensions().len(),
+            ),
+            &indices.borrow(),
+        )
+        .with_code("E0306")
+        .into()
+    })
+}
+
+/// Reject constant indices outside of the array's dimensions, unsized ones (`integer[]`
+/// parameters) are only checked for negative indices
+///
+/// The indexed type is still right, so unlike `index_type` this doesn't poison the variable
+fn check_bounds(name: &str, var_type: &VarType, indices: &CodeNode) -> CompilerResult<()> {
+    let dimensions = var_type.dimensions();
+
+    let errors = indices
+        .children()
+        .zip(dimensions)
+        .enumerate()
+        .filter_map(|(i, (index, &size))| {
+            let value = const_int(&index)?;
+
+            // Only worth saying which dimension if there's more than one
+            let array = match dimensions.len() {
+                1 => format!("array '{name}'"),
+                _ => format!("dimension {} of array '{name}'", i + 1),
+            };
+
+            let message = if value < 0 {
+                format!("Index {value} of {array} is negative!")
+            } else if size != 0 && value as usize >= size {
+                format!("Index {value} is out of bounds for {array} of size {size}!")
+            } else {
+                return None;
+            };
+
+            let error = CompilerError::new_with_node(message, &index.borrow())
+                .with_code("E0307")
+                .with_help(match size {
+                    0 => String::from("Indices start at 0"),
+                    _ => format!("Indices start at 0, so the last one is {}", size - 1),
+                });
+
+            Some(error)
+        })
+        .collect::<Vec<_>>();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn get_type_size(type_: &Type) -> usize {
+    if type_.eq_variant(&Type::IntNum(0)) || type_.eq(&Type::Integer) {
+        INT_SIZE
+    } else if type_.eq_variant(&Type::FloatNum(0.0)) || type_.eq(&Type::Float) {
+        FLOAT_SIZE
+    } else {
+        0 // classes depend on their layout, see `get_var_size`
+    }
+}
+
+/// Like `get_type_size`, but also knows the size of classes once their layout is done
+pub fn get_var_size(node: &CodeNode, type_: &Type) -> usize {
+    match type_ {
+        Type::Id(class_name) => with_global_table(node, |global| {
+            global.get(class_name).map(|c| c.borrow().size)
+        })
+        .ok()
+        .flatten()
+        .unwrap_or(0),
+        _ => get_type_size(type_),
     }
 }
@@ -0,0 +1,326 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue, StructNode, VarType},
+        symbol_table::SymbolTable,
+        tree_node::TreeNode,
+    },
+    compiler_error::{CompilerError, WarningKind},
+    lexical::tokens::token_type::Type,
+};
+
+use super::{
+    visitor::{misused_self, VisitorResult},
+    visitor_utils::{get_current_function_name, Signature},
+    xref::SymbolReference,
+};
+
+/// A function body, by its global table key
+struct FunctionUses {
+    /// The leaf (or scope) naming the function, where warnings point to
+    id_node: CodeNode,
+    /// Global table keys of every function (and constructor) it calls, or just `f(` for calls to
+    /// `f` that failed to resolve, which could be any of its overloads
+    calls: Vec<String>,
+}
+
+/// Warns about symbols that are declared but never used: locals and parameters whose value is never
+/// read in their function, free functions `main` never calls (even indirectly), and classes never
+/// instantiated
+///
+/// Locals built with a constructor count as used, since the constructor runs. A class counts as
+/// instantiated if it's the type of a local, or the base or attribute type of a class that is.
+/// Symbols whose name ends with `_` are never reported.
+pub struct UnusedSymbolChecker<'a> {
+    global: &'a SymbolTable,
+    /// What the `SymbolTableVisitor` resolved every name to
+    references: &'a [SymbolReference],
+    functions: HashMap<String, FunctionUses>,
+    instantiated: HashSet<String>,
+    errors: Vec<CompilerError>,
+}
+
+impl<'a> UnusedSymbolChecker<'a> {
+    pub fn new(global: &'a SymbolTable, references: &'a [SymbolReference]) -> Self {
+        Self {
+            global,
+            references,
+            functions: HashMap::new(),
+            instantiated: HashSet::new(),
+            errors: vec![],
+        }
+    }
+
+    pub fn check(&mut self, root: &CodeNode) -> VisitorResult {
+        for child in root.children() {
+            // Functions named `self` aren't in the global table, the symbol table pass reports them
+            if matches!(child.borrow().value, NodeValue::Tree(TreeNode::Function()))
+                && misused_self(&child).is_none()
+            {
+                self.check_function(&child);
+            }
+        }
+
+        self.check_functions();
+
+        let mut classes: HashSet<String> = HashSet::new();
+
+        for child in root.children() {
+            if !matches!(child.borrow().value, NodeValue::Tree(TreeNode::Class())) {
+                continue;
+            }
+
+            // Duplicate classes are reported by the collector, only the first one counts
+            let class_name = child.first_child().and_then(|id| id_name(&id));
+            if class_name.is_some_and(|class_name| classes.insert(class_name)) {
+                self.check_class(&child);
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    /// Report unused locals and parameters, and record what the function calls and instantiates
+    fn check_function(&mut self, function: &CodeNode) {
+        let (head, body) = match (function.first_child(), function.last_child()) {
+            (Some(head), Some(body)) => (head, body),
+            _ => return,
+        };
+        let mut head_children = head.children();
+        let (id_node, param_list) = match (head_children.next(), head_children.next()) {
+            (Some(id_node), Some(param_list)) => (id_node, param_list),
+            _ => return,
+        };
+
+        let name = get_current_function_name(&head).unwrap_or_default();
+        let key = Signature::from_nodes(&name, &param_list, None).to_string();
+
+        // Locals and parameters are only used by the references that resolved to them in the
+        // function's own scope, not by ones to a member with the same name or assignments
+        let scope = head
+            .borrow()
+            .symbol_table
+            .borrow()
+            .as_ref()
+            .map(|table| table.scope.clone());
+        let used = self
+            .references
+            .iter()
+            .filter(|r| !r.is_write && Some(&r.symbol.scope) == scope.as_ref())
+            .map(|r| r.symbol.name.as_str())
+            .collect::<HashSet<_>>();
+        // Nothing resolved in a function that failed to check
+        let is_unused =
+            |name: &str| scope.is_some() && !used.contains(name) && !name.ends_with('_');
+
+        for param in param_list.children() {
+            let id = match param.first_child() {
+                Some(id) => id,
+                None => continue,
+            };
+
+            if let Some(param_name) = id_name(&id) {
+                if is_unused(&param_name) {
+                    self.errors.push(unused_warning(
+                        "W0202",
+                        format!("Parameter '{param_name}' of '{name}' is never used"),
+                        "End its name with '_' if the function has to take it without using it",
+                        &id.borrow(),
+                    ));
+                }
+            }
+        }
+
+        let mut calls = vec![];
+
+        for node in body.descendants() {
+            let node_ref = node.borrow();
+
+            match &node_ref.value {
+                NodeValue::Tree(TreeNode::FunctionCall()) => {
+                    match (node_ref.target.borrow().clone(), node.first_child()) {
+                        (Some(target), _) => calls.push(target),
+                        (None, Some(id)) => calls.extend(id_name(&id).map(|f| format!("{f}("))),
+                        _ => (),
+                    }
+                }
+                NodeValue::Tree(TreeNode::LocalVarDecl()) => {
+                    let constructor = node_ref.target.borrow().clone();
+                    let is_constructed = constructor.is_some();
+                    calls.extend(constructor);
+
+                    let mut children = node.children();
+                    let (id, var_type) = match (children.next(), children.next()) {
+                        (Some(id), Some(var_type)) => (id, var_type),
+                        _ => continue,
+                    };
+
+                    if let Some(class_name) = id_name(&var_type) {
+                        self.instantiated.insert(class_name);
+                    }
+
+                    if let Some(var_name) = id_name(&id) {
+                        if !is_constructed && is_unused(&var_name) {
+                            self.errors.push(unused_warning(
+                                "W0201",
+                                format!("Local variable '{var_name}' of '{name}' is never used"),
+                                "Remove it, or end its name with '_' if it's meant to be unused",
+                                &id.borrow(),
+                            ));
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        // Duplicate definitions are reported by the collector, whatever they call still counts
+        self.functions
+            .entry(key)
+            .or_insert(FunctionUses {
+                id_node,
+                calls: vec![],
+            })
+            .calls
+            .extend(calls);
+    }
+
+    /// Report free functions `main` never reaches
+    fn check_functions(&mut self) {
+        let main = match self.functions.keys().find(|k| k.starts_with("main(")) {
+            Some(main) => main.clone(),
+            // Nothing is called without a main
+            None => return,
+        };
+
+        let mut reached = HashSet::from([main.clone()]);
+        let mut pending = vec![main];
+
+        while let Some(key) = pending.pop() {
+            let calls = match self.functions.get(&key) {
+                Some(function) => &function.calls,
+                None => continue,
+            };
+
+            let called = calls
+                .iter()
+                .flat_map(|call| {
+                    if call.ends_with('(') {
+                        self.functions
+                            .keys()
+                            .filter(|k| k.starts_with(call.as_str()))
+                            .cloned()
+                            .collect()
+                    } else {
+                        vec![call.clone()]
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            for call in called {
+                if reached.insert(call.clone()) {
+                    pending.push(call);
+                }
+            }
+        }
+
+        let mut unreached = self
+            .functions
+            .iter()
+            .filter(|(key, _)| !key.contains("::") && !reached.contains(*key))
+            .filter(|(key, _)| !key.split('(').next().unwrap_or_default().ends_with('_'))
+            .collect::<Vec<_>>();
+        unreached.sort_by_key(|(_, f)| f.id_node.borrow().token.location.clone());
+
+        for (key, function) in unreached {
+            self.errors.push(unused_warning(
+                "W0203",
+                format!("Function '{key}' is never called from 'main'"),
+                "Call it from 'main' (even indirectly), or end its name with '_' if it's meant to \
+                be unused",
+                &function.id_node.borrow(),
+            ));
+        }
+    }
+
+    /// Report the class if it (or a class containing or inheriting it) is never instantiated
+    fn check_class(&mut self, class: &CodeNode) {
+        let id = match class.first_child() {
+            Some(id) => id,
+            None => return,
+        };
+        let class_name = match id_name(&id) {
+            Some(class_name) => class_name,
+            None => return,
+        };
+
+        if class_name.ends_with('_') || self.is_used(&class_name) {
+            return;
+        }
+
+        self.errors.push(unused_warning(
+            "W0204",
+            format!("Class '{class_name}' is never instantiated"),
+            "Declare a variable of the class, or end its name with '_' if it's meant to be unused",
+            &id.borrow(),
+        ));
+    }
+
+    fn is_used(&self, class_name: &str) -> bool {
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut pending = self.instantiated.iter().cloned().collect::<Vec<_>>();
+
+        while let Some(used) = pending.pop() {
+            if used == class_name {
+                return true;
+            }
+
+            if !seen.insert(used.clone()) {
+                continue;
+            }
+
+            let class_data = match self.global.get(&used) {
+                Some(class_data) => class_data.borrow(),
+                None => continue,
+            };
+            let class_table = match class_data.table.as_ref() {
+                Some(class_table) => class_table,
+                None => continue,
+            };
+
+            pending.extend(class_table.bases.iter().cloned());
+
+            for member in class_table.values() {
+                if let VarType::Class(attribute_class, _) = &member.borrow().var_type {
+                    pending.push(attribute_class.clone());
+                }
+            }
+        }
+
+        false
+    }
+}
+
+fn unused_warning(
+    code: &'static str,
+    message: String,
+    help: &str,
+    node: &StructNode,
+) -> CompilerError {
+    CompilerError::new_with_node(message, node)
+        .with_code(code)
+        .warning(WarningKind::Unused)
+        .with_help(help.to_string())
+}
+
+fn id_name(node: &CodeNode) -> Option<String> {
+    match &node.borrow().value {
+        NodeValue::Leaf(Type::Id(name)) => Some(name.clone()),
+        _ => None,
+    }
+}
@@ -8,30 +8,122 @@ use crate::{
 };
 
 pub type VisitorResult = CompilerResult<()>;
+pub type EnterResult = CompilerResult<Traversal>;
+
+/// What to do after entering a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Traversal {
+    /// Visit the children, then the node itself
+    Descend,
+    /// Don't visit the children or the node, only exit it
+    Skip,
+}
 
 pub const INT_SIZE: usize = 4;
 pub const FLOAT_SIZE: usize = 8;
 
+/// The `self` leaf misused by `node`, if it's the declaration or statement that misuses it
+///
+/// `self` parses anywhere a name does, but it only refers to the object of a member function: it
+/// can start a variable (ie. `self.a`), but it can't name something else or be assigned to.
+pub fn misused_self(node: &CodeNode) -> Option<CodeNode> {
+    if !is_statement(node) {
+        return None;
+    }
+
+    node.descendants().find(|leaf| {
+        is_misused_self(leaf) && leaf.ancestors().find(is_statement).as_ref() == Some(node)
+    })
+}
+
+/// Whether `node` is a declaration or statement, ie. one of the items of a list
+fn is_statement(node: &CodeNode) -> bool {
+    node.parent().is_some_and(|parent| {
+        matches!(
+            parent.borrow().value,
+            NodeValue::Tree(
+                TreeNode::Program()
+                    | TreeNode::ClassMembers()
+                    | TreeNode::InheritsList()
+                    | TreeNode::ParameterList()
+                    | TreeNode::FunctionBody()
+                    | TreeNode::IfBlock()
+                    | TreeNode::ElseBlock()
+                    | TreeNode::WhileBlock()
+            )
+        )
+    })
+}
+
+fn is_misused_self(node: &CodeNode) -> bool {
+    if !matches!(node.borrow().value, NodeValue::Leaf(Type::SelfT)) {
+        return false;
+    }
+
+    match node.parent().filter(|_| node.previous_sibling().is_none()) {
+        // `self = ...` or `read(self)`, the object can only be changed through its members
+        Some(variable)
+            if matches!(
+                variable.borrow().value,
+                NodeValue::Tree(TreeNode::Variable())
+            ) =>
+        {
+            variable.previous_sibling().is_none()
+                && variable.parent().is_some_and(|parent| {
+                    matches!(
+                        parent.borrow().value,
+                        NodeValue::Tree(TreeNode::Assignment() | TreeNode::Read())
+                    )
+                })
+        }
+        _ => true,
+    }
+}
+
 pub trait Visitor {
     fn visit(&mut self, node: &CodeNode) -> VisitorResult {
         let mut errors: Vec<CompilerError> = Vec::new();
 
-        for child in node.children() {
-            if let Err(e) = self.visit(&child) {
+        if let Some(self_leaf) = misused_self(node) {
+            // Left out entirely, so nothing it declares is missing or reported again later on
+            return self.visit_misused_self(node, &self_leaf);
+        }
+
+        let value = node.borrow().value.clone();
+        let traversal = match &value {
+            NodeValue::Tree(t) => self.enter_tree(node, t),
+            _ => Ok(Traversal::Descend),
+        }
+        .unwrap_or_else(|e| {
+            errors.extend(e);
+            Traversal::Descend
+        });
+
+        if traversal == Traversal::Descend {
+            for child in node.children() {
+                if let Err(e) = self.visit(&child) {
+                    errors.extend(e);
+                };
+            }
+
+            if let Err(e) = match &value {
+                NodeValue::Leaf(l) => self.visit_leaf(node, l),
+                NodeValue::Tree(t) => self.visit_tree(node, t),
+                NodeValue::Marker => Err(vec![CompilerError::new(
+                    "Unexpected marker node!".to_string(),
+                    node.borrow().token.clone(),
+                )]),
+            } {
                 errors.extend(e);
             };
         }
 
-        if let Err(e) = match &node.borrow().value {
-            NodeValue::Leaf(l) => self.visit_leaf(node, l),
-            NodeValue::Tree(t) => self.visit_tree(node, t),
-            NodeValue::Marker => Err(vec![CompilerError::new(
-                "Unexpected marker node!".to_string(),
-                node.borrow().token.clone(),
-            )]),
-        } {
-            errors.extend(e);
-        };
+        // Always exit, even if skipped, so anything pushed on enter can be popped
+        if let NodeValue::Tree(t) = &value {
+            if let Err(e) = self.exit_tree(node, t) {
+                errors.extend(e);
+            }
+        }
 
         if errors.is_empty() {
             Ok(())
@@ -40,6 +132,355 @@ pub trait Visitor {
         }
     }
 
+    /// Called instead of visiting a declaration or statement that misuses `self`
+    fn visit_misused_self(&mut self, _node: &CodeNode, _self_leaf: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    /// Called on the way down, before any children are visited
+    ///
+    /// Returning `Traversal::Skip` skips the children *and* the node's own `visit_*`
+    fn enter_tree(&mut self, node: &CodeNode, tree_node: &TreeNode) -> EnterResult {
+        match tree_node {
+            TreeNode::ArgumentList() => self.enter_argument_list(node),
+            TreeNode::ArithExpr() => self.enter_arith_expr(node),
+            TreeNode::Assignment() => self.enter_assignment(node),
+            TreeNode::Attribute() => self.enter_attribute(node),
+            TreeNode::Class() => self.enter_class(node),
+            TreeNode::ClassMembers() => self.enter_class_members(node),
+            TreeNode::ConstructorFunc() => self.enter_constructor_func(node),
+            TreeNode::ElseBlock() => self.enter_else_block(node),
+            TreeNode::Expr() => self.enter_expr(node),
+            TreeNode::Factor() => self.enter_factor(node),
+            TreeNode::Function() => self.enter_function(node),
+            TreeNode::FunctionBody() => self.enter_function_body(node),
+            TreeNode::FunctionCall() => self.enter_function_call(node),
+            TreeNode::FunctionHead() => self.enter_function_head(node),
+            TreeNode::If() => self.enter_if(node),
+            TreeNode::IfBlock() => self.enter_if_block(node),
+            TreeNode::IndexedVar() => self.enter_indexed_var(node),
+            TreeNode::IndiceList() => self.enter_indice_list(node),
+            TreeNode::InheritsList() => self.enter_inherits_list(node),
+            TreeNode::LocalVarDecl() => self.enter_local_var_decl(node),
+            TreeNode::MemberFunc() => self.enter_member_func(node),
+            TreeNode::NestedVar() => self.enter_nested_var(node),
+            TreeNode::Parameter() => self.enter_parameter(node),
+            TreeNode::ParameterList() => self.enter_parameter_list(node),
+            TreeNode::Program() => self.enter_program(node),
+            TreeNode::Read() => self.enter_read(node),
+            TreeNode::RelExpr() => self.enter_rel_expr(node),
+            TreeNode::Return() => self.enter_return(node),
+            TreeNode::Scope() => self.enter_scope(node),
+            TreeNode::Variable() => self.enter_variable(node),
+            TreeNode::While() => self.enter_while(node),
+            TreeNode::WhileBlock() => self.enter_while_block(node),
+            TreeNode::Write() => self.enter_write(node),
+        }
+    }
+
+    /// Called on the way back up, after the node's `visit_*` (or right after `enter_*` if skipped)
+    fn exit_tree(&mut self, node: &CodeNode, tree_node: &TreeNode) -> VisitorResult {
+        match tree_node {
+            TreeNode::ArgumentList() => self.exit_argument_list(node),
+            TreeNode::ArithExpr() => self.exit_arith_expr(node),
+            TreeNode::Assignment() => self.exit_assignment(node),
+            TreeNode::Attribute() => self.exit_attribute(node),
+            TreeNode::Class() => self.exit_class(node),
+            TreeNode::ClassMembers() => self.exit_class_members(node),
+            TreeNode::ConstructorFunc() => self.exit_constructor_func(node),
+            TreeNode::ElseBlock() => self.exit_else_block(node),
+            TreeNode::Expr() => self.exit_expr(node),
+            TreeNode::Factor() => self.exit_factor(node),
+            TreeNode::Function() => self.exit_function(node),
+            TreeNode::FunctionBody() => self.exit_function_body(node),
+            TreeNode::FunctionCall() => self.exit_function_call(node),
+            TreeNode::FunctionHead() => self.exit_function_head(node),
+            TreeNode::If() => self.exit_if(node),
+            TreeNode::IfBlock() => self.exit_if_block(node),
+            TreeNode::IndexedVar() => self.exit_indexed_var(node),
+            TreeNode::IndiceList() => self.exit_indice_list(node),
+            TreeNode::InheritsList() => self.exit_inherits_list(node),
+            TreeNode::LocalVarDecl() => self.exit_local_var_decl(node),
+            TreeNode::MemberFunc() => self.exit_member_func(node),
+            TreeNode::NestedVar() => self.exit_nested_var(node),
+            TreeNode::Parameter() => self.exit_parameter(node),
+            TreeNode::ParameterList() => self.exit_parameter_list(node),
+            TreeNode::Program() => self.exit_program(node),
+            TreeNode::Read() => self.exit_read(node),
+            TreeNode::RelExpr() => self.exit_rel_expr(node),
+            TreeNode::Return() => self.exit_return(node),
+            TreeNode::Scope() => self.exit_scope(node),
+            TreeNode::Variable() => self.exit_variable(node),
+            TreeNode::While() => self.exit_while(node),
+            TreeNode::WhileBlock() => self.exit_while_block(node),
+            TreeNode::Write() => self.exit_write(node),
+        }
+    }
+
+    fn enter_argument_list(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_argument_list(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_arith_expr(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_arith_expr(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_assignment(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_assignment(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_attribute(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_attribute(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_class(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_class(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_class_members(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_class_members(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_constructor_func(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_constructor_func(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_else_block(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_else_block(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_expr(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_expr(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_factor(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_factor(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_function(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_function(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_function_body(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_function_body(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_function_call(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_function_call(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_function_head(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_function_head(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_if(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_if(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_if_block(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_if_block(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_indexed_var(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_indexed_var(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_indice_list(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_indice_list(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_inherits_list(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_inherits_list(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_local_var_decl(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_local_var_decl(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_member_func(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_member_func(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_nested_var(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_nested_var(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_parameter(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_parameter(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_parameter_list(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_parameter_list(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_program(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_program(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_read(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_read(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_rel_expr(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_rel_expr(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_return(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_return(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_scope(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_scope(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_variable(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_variable(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_while(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_while(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_while_block(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_while_block(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
+    fn enter_write(&mut self, _node: &CodeNode) -> EnterResult {
+        Ok(Traversal::Descend)
+    }
+
+    fn exit_write(&mut self, _node: &CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
     fn visit_leaf(&mut self, _node: &CodeNode, _type_node: &Type) -> VisitorResult {
         Ok(())
     }
@@ -80,24 +521,42 @@ pub trait Visitor {
             ),
             TreeNode::ElseBlock() => self.visit_else_block(node, children.collect()),
             TreeNode::Expr() => self.visit_expr(node, children.collect()),
-            TreeNode::Factor() => self.visit_factor(node, children.next().unwrap().try_into()?),
+            TreeNode::Factor() => {
+                let first = children.next().unwrap();
+
+                match children.next() {
+                    Some(factor) => self.visit_unary_factor(node, first.try_into()?, factor),
+                    None if matches!(first.borrow().value, NodeValue::Tree(_)) => {
+                        self.visit_paren_factor(node, first)
+                    }
+                    None => self.visit_factor(node, first.try_into()?),
+                }
+            }
             TreeNode::Function() => {
                 self.visit_function(node, children.next().unwrap(), children.next().unwrap())
             }
             TreeNode::FunctionBody() => self.visit_function_body(node, children.collect()),
-            TreeNode::FunctionCall() => self.visit_function_call(
-                node,
-                children.next().unwrap().try_into()?,
-                children.next().unwrap(),
-            ),
+            TreeNode::FunctionCall() => {
+                // Member function calls start with whatever they're called on, `a.b.f()`
+                let object = match node.first_child() {
+                    Some(first) if matches!(first.borrow().value, NodeValue::Tree(_)) => {
+                        children.next()
+                    }
+                    _ => None,
+                };
+
+                self.visit_function_call(
+                    node,
+                    object,
+                    children.next().unwrap().try_into()?,
+                    children.next().unwrap(),
+                )
+            }
             TreeNode::FunctionHead() => self.visit_function_head(
                 node,
                 children.next().unwrap(),
                 children.next().unwrap(),
-                children
-                    .next()
-                    .map(|c| c.try_into())
-                    .map_or(Ok(None), |r| r.map(Some))?,
+                children.next().map(Type::try_from).transpose()?,
             ),
             TreeNode::If() => self.visit_if(
                 node,
@@ -124,6 +583,7 @@ pub trait Visitor {
             ),
             TreeNode::NestedVar() => self.visit_nested_var(
                 node,
+                children.next().unwrap(),
                 children.next().unwrap().try_into()?,
                 children.next().unwrap(),
             ),
@@ -235,6 +695,21 @@ pub trait Visitor {
         Ok(())
     }
 
+    /// A sign or `not` applied to a factor, `-x` or `not x`
+    fn visit_unary_factor(
+        &mut self,
+        _node: &CodeNode,
+        _op: Type,
+        _factor: CodeNode,
+    ) -> VisitorResult {
+        Ok(())
+    }
+
+    /// An arithmetic expression in parentheses, `(a + b)`
+    fn visit_paren_factor(&mut self, _node: &CodeNode, _expr: CodeNode) -> VisitorResult {
+        Ok(())
+    }
+
     fn visit_function(
         &mut self,
         _node: &CodeNode,
@@ -251,6 +726,7 @@ pub trait Visitor {
     fn visit_function_call(
         &mut self,
         _node: &CodeNode,
+        _object: Option<CodeNode>,
         _id: Type,
         _param_list: CodeNode,
     ) -> VisitorResult {
@@ -314,9 +790,11 @@ pub trait Visitor {
         Ok(())
     }
 
+    /// A member access, `object.id[indices]`, where `object` is a `Variable`, `NestedVar` or `FunctionCall`
     fn visit_nested_var(
         &mut self,
         _node: &CodeNode,
+        _object: CodeNode,
         _id: Type,
         _indices: CodeNode,
     ) -> VisitorResult {
@@ -1,27 +1,29 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
 
 use crate::{
     ast::{
-        nodes::{CodeNode, NodeValue, SymbolData, SymbolTable},
+        nodes::{CodeNode, NodeValue, VarType},
+        symbol_table::{SymbolData, SymbolTable},
         tree_node::TreeNode,
     },
     compiler_error::{CompilerError, CompilerResult},
+    lexical::tokens::{location::Location, token_type::Type},
 };
 
+/// Look `id` up from the scope of the function `start` is in, then the scopes around it
 pub fn get_symbol_data(start: &CodeNode, id: &str) -> Option<Rc<RefCell<SymbolData>>> {
-    let mut parent = start.parent();
-
-    while let Some(p) = parent {
-        if let Some(table) = p.borrow().symbol_table.borrow().clone() {
-            if let Some(info) = table.get(id) {
-                return Some(info.clone());
-            }
-        }
+    let function = get_current_function(start)?;
 
-        parent = p.parent();
-    }
-
-    None
+    let function_ref = function.borrow();
+    let table_ref = function_ref.symbol_table.borrow();
+    let table = table_ref.as_ref()?;
+    with_global_table(start, |global| table.lookup(id, global))
+        .ok()
+        .flatten()
 }
 
 pub fn get_current_function(start: &CodeNode) -> Option<CodeNode> {
@@ -38,13 +40,20 @@ pub fn get_current_function(start: &CodeNode) -> Option<CodeNode> {
     None
 }
 
-pub fn get_global_table(start: &CodeNode) -> CompilerResult<SymbolTable> {
+/// Run `f` on the global table, borrowed from the `Program` node `start` is in
+pub fn with_global_table<T>(
+    start: &CodeNode,
+    f: impl FnOnce(&SymbolTable) -> T,
+) -> CompilerResult<T> {
     let mut parent = start.parent();
 
     while let Some(p) = parent {
         if let NodeValue::Tree(TreeNode::Program()) = p.borrow().value {
-            if let Some(table) = p.borrow().symbol_table.borrow().clone() {
-                return Ok(table);
+            let p_ref = p.borrow();
+            let table_ref = p_ref.symbol_table.borrow();
+
+            if let Some(table) = table_ref.as_ref() {
+                return Ok(f(table));
             }
         }
 
@@ -57,3 +66,250 @@ pub fn get_global_table(start: &CodeNode) -> CompilerResult<SymbolTable> {
     )
     .into())
 }
+
+/// The dimensions of a declaration like `arr: integer[4][]`, taken from its `IndiceList`
+///
+/// Unsized dimensions (`[]`) are `0`, and are only allowed if `allow_unsized` (ie. for parameters)
+pub fn get_declared_dimensions(
+    indices: &CodeNode,
+    allow_unsized: bool,
+) -> CompilerResult<Vec<usize>> {
+    indices
+        .children()
+        .map(|num| match &num.borrow().value {
+            NodeValue::Leaf(Type::IntNum(n)) => usize::try_from(*n).map_err(|_| {
+                CompilerError::new("Expected usize!".to_string(), num.borrow().token.clone()).into()
+            }),
+            NodeValue::Leaf(Type::CloseSqbr) if allow_unsized => Ok(0),
+            NodeValue::Leaf(Type::CloseSqbr) => Err(CompilerError::new(
+                "Only parameters can be arrays without a size!".to_string(),
+                num.borrow().token.clone(),
+            )
+            .with_code("E0113")
+            .into()),
+            _ => Err(CompilerError::new(
+                "Expected number!".to_string(),
+                num.borrow().token.clone(),
+            )
+            .into()),
+        })
+        .collect()
+}
+
+/// Where the name declared by `node` (a class, member, function head, parameter or local) is
+///
+/// Member function heads (`function A::f()`) point to the function's name, not the class'
+pub fn get_declared_location(node: &CodeNode) -> Option<Location> {
+    node.children()
+        .find_map(|child| match &child.borrow().value {
+            NodeValue::Leaf(Type::Id(_) | Type::Constructor) => {
+                Some(child.borrow().token.location.clone())
+            }
+            NodeValue::Tree(TreeNode::Scope()) => {
+                Some(child.last_child()?.borrow().token.location.clone())
+            }
+            _ => None,
+        })
+}
+
+/// The name of the function `start` is in, like `main` or `A::f`
+pub fn get_current_function_name(start: &CodeNode) -> Option<String> {
+    let func = get_current_function(start)?;
+    let head = func.first_child()?;
+    let head_id = head.first_child()?;
+    let head_id_ref = head_id.borrow();
+
+    let leaf_name = |node: &CodeNode| match &node.borrow().value {
+        NodeValue::Leaf(Type::Id(id)) => Some(id.clone()),
+        NodeValue::Leaf(Type::Constructor) => Some(String::from("constructor")),
+        _ => None,
+    };
+
+    match &head_id_ref.value {
+        NodeValue::Tree(TreeNode::Scope()) => Some(
+            head_id
+                .children()
+                .filter_map(|c| leaf_name(&c))
+                .collect::<Vec<_>>()
+                .join("::"),
+        ),
+        _ => leaf_name(&head_id),
+    }
+}
+
+/// The class of the member function (`function A::f()`) that `start` is in, if any
+pub fn get_current_class(start: &CodeNode) -> Option<String> {
+    let func = get_current_function(start)?;
+    let head = func.first_child()?;
+    let head_id = head.first_child()?;
+
+    if let NodeValue::Tree(TreeNode::Scope()) = head_id.borrow().value {
+        let class_id = head_id.first_child()?;
+        let class_ref = class_id.borrow();
+
+        if let NodeValue::Leaf(Type::Id(class_name)) = &class_ref.value {
+            return Some(class_name.clone());
+        }
+    }
+
+    None
+}
+
+/// Find a member of a class, looking through the classes it inherits from if it's not declared there
+///
+/// Bases are searched depth-first in `isa` order. Returns the class that declares the member
+/// along with its data
+///
+/// Member functions are keyed by signature, so they can be found by their full key (`f(Integer)`)
+/// or by their name. A name finds one of the overloads declared in the first class that has any,
+/// which is enough to know who declares it, `resolve_overload` picks the one that's called.
+pub fn get_class_member(
+    global: &SymbolTable,
+    class_name: &str,
+    member: &str,
+) -> Option<(String, Rc<RefCell<SymbolData>>)> {
+    let mut to_search = vec![class_name.to_string()];
+    let mut searched: Vec<String> = vec![];
+
+    while let Some(class_name) = to_search.pop() {
+        // Inheritance cycles are reported elsewhere, just don't loop forever
+        if searched.contains(&class_name) {
+            continue;
+        }
+
+        let class_data = global
+            .get(&class_name)
+            .map(|class_data| class_data.borrow());
+
+        if let Some(class_table) = class_data.as_ref().and_then(|d| d.table.as_ref()) {
+            if let Some(data) = class_table.get(member) {
+                return Some((class_name, data.clone()));
+            }
+
+            let overload = class_table
+                .iter()
+                .filter(|(key, _)| key.contains('(') && member_name(key) == member)
+                .min_by(|(a, _), (b, _)| a.cmp(b));
+            if let Some((_, data)) = overload {
+                return Some((class_name, data.clone()));
+            }
+
+            to_search.extend(class_table.bases.iter().rev().cloned());
+        }
+
+        searched.push(class_name);
+    }
+
+    None
+}
+
+/// The types of the parameters in a `ParameterList`, once symbol_globals typed them
+pub fn get_param_types(param_list: &CodeNode) -> Vec<Option<VarType>> {
+    param_list
+        .children()
+        .map(|param| {
+            param
+                .borrow()
+                .symbol_table
+                .borrow()
+                .as_ref()
+                .and_then(|t| t.values().next().map(|d| d.borrow().var_type.clone()))
+        })
+        .collect()
+}
+
+/// The name of a class member, without the parameters member functions are keyed with
+pub fn member_name(key: &str) -> &str {
+    key.split('(').next().unwrap_or(key)
+}
+
+/// Offset of the `base` part of objects of `class_name`, where `self` points when one of `base`'s
+/// member functions is called on them
+///
+/// Objects start with their bases in `isa` order, so only the first one is at offset 0. Bases are
+/// searched like `get_class_member` does, `None` if `class_name` doesn't inherit from `base`.
+pub fn get_base_offset(global: &SymbolTable, class_name: &str, base: &str) -> Option<isize> {
+    fn search(
+        global: &SymbolTable,
+        class_name: &str,
+        base: &str,
+        searched: &mut Vec<String>,
+    ) -> Option<isize> {
+        if class_name == base {
+            return Some(0);
+        }
+
+        // Inheritance cycles are reported elsewhere, just don't loop forever
+        if searched.iter().any(|c| c == class_name) {
+            return None;
+        }
+        searched.push(class_name.to_string());
+
+        let class_data = global.get(class_name)?.borrow();
+        let class_table = class_data.table.as_ref()?;
+        let mut size = 0;
+
+        for class_base in &class_table.bases {
+            if let Some(offset) = search(global, class_base, base, searched) {
+                return Some(offset - size);
+            }
+
+            size += global
+                .get(class_base)
+                .map_or(0, |b| b.borrow().size as isize);
+        }
+
+        None
+    }
+
+    search(global, class_name, base, &mut vec![])
+}
+
+/// A function as it's declared or defined, ie. `f(Integer, Float[2]) => Float`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub params: Vec<String>,
+    /// Constructors don't have one
+    pub return_type: Option<String>,
+}
+
+impl Signature {
+    /// Build a signature from a `ParameterList` and a return type leaf, once symbol_globals typed
+    /// the parameters
+    pub fn from_nodes(name: &str, param_list: &CodeNode, return_type: Option<&CodeNode>) -> Self {
+        let params = get_param_types(param_list)
+            .iter()
+            .map(|param| match param {
+                Some(param) => param.to_string(),
+                None => String::from("?"),
+            })
+            .collect();
+
+        let return_type = return_type.map(|r| match &r.borrow().value {
+            NodeValue::Leaf(Type::Integer) => VarType::Integer(vec![]).to_string(),
+            NodeValue::Leaf(Type::Float) => VarType::Float(vec![]).to_string(),
+            NodeValue::Leaf(Type::Void) => VarType::Void.to_string(),
+            NodeValue::Leaf(Type::Id(id)) => VarType::Class(id.clone(), vec![]).to_string(),
+            _ => String::from("?"),
+        });
+
+        Self {
+            name: name.to_string(),
+            params,
+            return_type,
+        }
+    }
+}
+
+impl Display for Signature {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}({})", self.name, self.params.join(", "))?;
+
+        if let Some(return_type) = &self.return_type {
+            write!(f, " => {return_type}")?;
+        }
+
+        Ok(())
+    }
+}
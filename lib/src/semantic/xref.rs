@@ -0,0 +1,314 @@
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+};
+
+use crate::{
+    ast::{
+        nodes::{CodeNode, NodeValue},
+        symbol_table::{ScopeKind, SymbolData, SymbolKind, SymbolTable},
+        tree_node::TreeNode,
+    },
+    lexical::tokens::{location::Location, span::Span, token_type::Type},
+};
+
+/// A declared symbol, by the scope it's declared in and its key in that scope's table
+///
+/// Member functions are global symbols (`A::f(Integer)`), like free functions, since that's where
+/// calls resolve to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SymbolId {
+    pub scope: ScopeKind,
+    pub name: String,
+}
+
+impl SymbolId {
+    pub fn new(scope: ScopeKind, name: &str) -> Self {
+        Self {
+            scope,
+            name: name.to_string(),
+        }
+    }
+}
+
+impl Display for SymbolId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.scope {
+            ScopeKind::Global => write!(f, "{}", self.name),
+            scope => write!(f, "{}::{}", scope.name(), self.name),
+        }
+    }
+}
+
+/// A use of a symbol, found while resolving names
+#[derive(Debug, Clone)]
+pub struct SymbolReference {
+    pub symbol: SymbolId,
+    /// The identifier that refers to it
+    pub span: Span,
+    /// Whether it's only assigned to (`a = ...` or `read(a)`), its value isn't used
+    pub is_write: bool,
+}
+
+/// Where a symbol is defined and everywhere it's used
+#[derive(Debug, Clone)]
+pub struct XrefEntry {
+    pub symbol: SymbolId,
+    pub kind: SymbolKind,
+    /// Where a member function is declared in its class, when it's defined outside of it
+    pub declaration: Option<Span>,
+    pub definition: Span,
+    /// In source order
+    pub references: Vec<Span>,
+}
+
+impl XrefEntry {
+    /// Whether the identifier at `location` is this symbol
+    pub fn contains(&self, location: &Location) -> bool {
+        self.definition.contains(location)
+            || self
+                .declaration
+                .as_ref()
+                .is_some_and(|d| d.contains(location))
+            || self.references.iter().any(|r| r.contains(location))
+    }
+}
+
+impl Display for XrefEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}), defined at {}",
+            self.symbol,
+            self.kind,
+            fmt_location(&self.definition.start)
+        )?;
+
+        if let Some(declaration) = &self.declaration {
+            write!(f, ", declared at {}", fmt_location(&declaration.start))?;
+        }
+
+        if self.references.is_empty() {
+            write!(f, ", never used")
+        } else {
+            write!(
+                f,
+                ", used at {}",
+                self.references
+                    .iter()
+                    .map(|r| fmt_location(&r.start))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+    }
+}
+
+/// Every symbol declared in the program (classes, attributes, functions, member functions,
+/// parameters and locals) with its definition and references, in the order they're defined
+///
+/// Symbols the compiler adds itself (like `self`) aren't in the index.
+#[derive(Debug, Clone, Default)]
+pub struct XrefIndex {
+    entries: Vec<XrefEntry>,
+}
+
+impl XrefIndex {
+    /// Build the index from the complete global table and the references found by the
+    /// `SymbolTableVisitor`
+    ///
+    /// Class names used as types (of attributes, parameters, locals, return types and in `isa`
+    /// lists) are found in the tree, since they don't need resolving.
+    pub fn build(root: &CodeNode, global: &SymbolTable, references: &[SymbolReference]) -> Self {
+        let mut entries: Vec<XrefEntry> = vec![];
+
+        for (name, data) in global.iter() {
+            push_entry(
+                &mut entries,
+                SymbolId::new(ScopeKind::Global, name),
+                &data.borrow(),
+            );
+        }
+
+        for (name, data) in global.iter() {
+            let data = data.borrow();
+
+            match (data.kind, &data.table) {
+                (SymbolKind::Class, Some(class_table)) => {
+                    add_class_members(&mut entries, name, class_table)
+                }
+                (SymbolKind::Function, Some(func_table)) => {
+                    for (local_name, local_data) in func_table.iter() {
+                        push_entry(
+                            &mut entries,
+                            SymbolId::new(ScopeKind::Function(name.clone()), local_name),
+                            &local_data.borrow(),
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        entries.sort_by(|e1, e2| {
+            e1.definition
+                .start
+                .cmp(&e2.definition.start)
+                .then_with(|| e1.symbol.to_string().cmp(&e2.symbol.to_string()))
+        });
+
+        let by_symbol = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.symbol.clone(), i))
+            .collect::<HashMap<_, _>>();
+
+        let type_references = root
+            .descendants()
+            .flat_map(|node| class_references(&node, global))
+            .collect::<Vec<_>>();
+
+        for reference in references.iter().chain(type_references.iter()) {
+            if let Some(&i) = by_symbol.get(&reference.symbol) {
+                entries[i].references.push(reference.span.clone());
+            }
+        }
+
+        for entry in entries.iter_mut() {
+            entry.references.sort_by(|r1, r2| r1.start.cmp(&r2.start));
+            entry.references.dedup();
+        }
+
+        Self { entries }
+    }
+
+    pub fn get(&self, symbol: &SymbolId) -> Option<&XrefEntry> {
+        self.entries.iter().find(|e| e.symbol == *symbol)
+    }
+
+    /// The symbol whose identifier (definition or reference) is at `location`
+    pub fn symbol_at(&self, location: &Location) -> Option<&XrefEntry> {
+        self.entries.iter().find(|e| e.contains(location))
+    }
+
+    pub fn entries(&self) -> &[XrefEntry] {
+        &self.entries
+    }
+}
+
+impl Display for XrefIndex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Add the symbol if the program declares it, symbols without a location were added by the compiler
+fn push_entry(entries: &mut Vec<XrefEntry>, symbol: SymbolId, data: &SymbolData) {
+    let location = match &data.location {
+        Some(location) => location.clone(),
+        None => return,
+    };
+
+    entries.push(XrefEntry {
+        definition: identifier_span(location, &symbol.name),
+        symbol,
+        kind: data.kind,
+        declaration: None,
+        references: vec![],
+    });
+}
+
+/// Attributes, and member functions that aren't defined anywhere
+///
+/// Member functions that are defined get the location of their declaration in the class instead.
+fn add_class_members(entries: &mut Vec<XrefEntry>, class_name: &str, class_table: &SymbolTable) {
+    for (name, data) in class_table.iter() {
+        let data = data.borrow();
+
+        if data.kind != SymbolKind::Function {
+            push_entry(
+                entries,
+                SymbolId::new(ScopeKind::Class(class_name.to_string()), name),
+                &data,
+            );
+            continue;
+        }
+
+        // Member functions are declared by their signature, like their definitions
+        let defined = |key: &str| key == format!("{class_name}::{name}");
+
+        let declaration = data
+            .location
+            .clone()
+            .map(|location| identifier_span(location, name));
+        let mut is_defined = false;
+
+        for entry in entries.iter_mut() {
+            if entry.symbol.scope == ScopeKind::Global && defined(&entry.symbol.name) {
+                entry.declaration = declaration.clone();
+                is_defined = true;
+            }
+        }
+
+        if !is_defined {
+            push_entry(
+                entries,
+                SymbolId::new(ScopeKind::Class(class_name.to_string()), name),
+                &data,
+            );
+        }
+    }
+}
+
+/// Class names used as types by `node`
+fn class_references(node: &CodeNode, global: &SymbolTable) -> Vec<SymbolReference> {
+    let type_nodes: Vec<CodeNode> = match &node.borrow().value {
+        NodeValue::Tree(TreeNode::InheritsList()) => node.children().collect(),
+        NodeValue::Tree(TreeNode::Attribute()) => node.children().nth(2).into_iter().collect(),
+        NodeValue::Tree(TreeNode::Parameter() | TreeNode::LocalVarDecl()) => {
+            node.children().nth(1).into_iter().collect()
+        }
+        NodeValue::Tree(TreeNode::MemberFunc() | TreeNode::FunctionHead()) => {
+            node.last_child().into_iter().collect()
+        }
+        // The class of a member function definition, `function A::f()`
+        NodeValue::Tree(TreeNode::Scope()) => node.first_child().into_iter().collect(),
+        _ => vec![],
+    };
+
+    type_nodes
+        .iter()
+        .filter_map(|type_node| {
+            let type_ref = type_node.borrow();
+
+            match &type_ref.value {
+                NodeValue::Leaf(Type::Id(class_name)) if global.contains_key(class_name) => {
+                    Some(SymbolReference {
+                        symbol: SymbolId::new(ScopeKind::Global, class_name),
+                        span: type_ref.span.clone(),
+                        is_write: false,
+                    })
+                }
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// The span of the identifier declaring `key`, which for functions is only the name part of
+/// `A::f(Integer)`
+fn identifier_span(start: Location, key: &str) -> Span {
+    let name = key.split('(').next().unwrap_or_default();
+    let name = name.rsplit("::").next().unwrap_or_default();
+    let end = Location::new(start.line, start.column + name.chars().count());
+
+    Span::new(start, end)
+}
+
+fn fmt_location(location: &Location) -> String {
+    format!("{}:{}", location.line, location.column)
+}
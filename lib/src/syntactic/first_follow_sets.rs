@@ -164,6 +164,10 @@ pub fn get_first_set_table() -> HashMap<&'static str, Vec<Type>> {
         ("INDICE", vec![Type::OpenSqbr]),
         ("STATEMENTIDNEST2", vec![Type::Dot]),
         ("STATEMENTIDNEST3", vec![Type::Dot, Type::Assign]),
+        (
+            "STATEMENTIDNEST4",
+            vec![Type::Dot, Type::OpenPar, Type::OpenSqbr, Type::Assign],
+        ),
         ("ASSIGNOP", vec![Type::Assign]),
         (
             "EXPR",
@@ -527,6 +531,7 @@ pub fn get_follow_set_table() -> HashMap<&'static str, Vec<Type>> {
         ),
         ("STATEMENTIDNEST2", vec![Type::Semi]),
         ("STATEMENTIDNEST3", vec![Type::Semi]),
+        ("STATEMENTIDNEST4", vec![Type::Semi]),
         (
             "ASSIGNOP",
             vec![
@@ -202,8 +202,9 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
         (
             ("ARRAYSIZE2", Type::CloseSqbr),
             vec![
-                // Production::Action(create_marker()), TODO: is `private attribute z: float[];` okay?
+                // Unsized, like `arr: integer[]`, only allowed for parameters
                 Production::Term(Type::CloseSqbr),
+                Production::Action(create_leaf()),
             ],
         ),
         (
@@ -314,42 +315,54 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
             ("EXPR2", Type::GEq),
             vec![
                 Production::NonTerm("RELOP"),
+                Production::Action(create_leaf()),
                 Production::NonTerm("ARITHEXPR"),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::RelExpr, 3)),
             ],
         ),
         (
             ("EXPR2", Type::LEq),
             vec![
                 Production::NonTerm("RELOP"),
+                Production::Action(create_leaf()),
                 Production::NonTerm("ARITHEXPR"),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::RelExpr, 3)),
             ],
         ),
         (
             ("EXPR2", Type::Gt),
             vec![
                 Production::NonTerm("RELOP"),
+                Production::Action(create_leaf()),
                 Production::NonTerm("ARITHEXPR"),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::RelExpr, 3)),
             ],
         ),
         (
             ("EXPR2", Type::Lt),
             vec![
                 Production::NonTerm("RELOP"),
+                Production::Action(create_leaf()),
                 Production::NonTerm("ARITHEXPR"),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::RelExpr, 3)),
             ],
         ),
         (
             ("EXPR2", Type::NotEq),
             vec![
                 Production::NonTerm("RELOP"),
+                Production::Action(create_leaf()),
                 Production::NonTerm("ARITHEXPR"),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::RelExpr, 3)),
             ],
         ),
         (
             ("EXPR2", Type::Eq),
             vec![
                 Production::NonTerm("RELOP"),
+                Production::Action(create_leaf()),
                 Production::NonTerm("ARITHEXPR"),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::RelExpr, 3)),
             ],
         ),
         (
@@ -706,87 +719,178 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
                 Production::NonTerm("IDNEST2"),
             ],
         ),
-        (
-            ("IDNEST2", Type::ClosePar),
-            vec![Production::NonTerm("REPTIDNEST1")],
-        ),
         (
             ("IDNEST2", Type::OpenPar),
             vec![
                 Production::Term(Type::OpenPar),
                 Production::Action(create_marker()),
                 Production::NonTerm("APARAMS"),
-                Production::Action(create_subtree_until_marker(TreeNode::ArgumentList)),
+                Production::Action(create_subtree_until_marker(TreeNode::ParameterList)),
                 Production::Term(Type::ClosePar),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::FunctionCall, 3)),
+            ],
+        ),
+        (
+            ("IDNEST2", Type::ClosePar),
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
             ],
         ),
         (
             ("IDNEST2", Type::Dot),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Semi),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Minus),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Plus),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Comma),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::GEq),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::LEq),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Gt),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Lt),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::NotEq),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Eq),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::And),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Div),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Mult),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::CloseSqbr),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::OpenSqbr),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("IDNEST2", Type::Or),
-            vec![Production::NonTerm("REPTIDNEST1")],
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
         ),
         (
             ("INDICE", Type::OpenSqbr),
@@ -1474,29 +1578,31 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
         (
             ("STATEMENTIDNEST", Type::Dot),
             vec![
-                Production::Term(Type::Dot),
-                Production::Term(Type::Id(String::from(""))),
-                Production::Action(create_leaf()),
-                Production::NonTerm("STATEMENTIDNEST"),
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_until_marker(TreeNode::Variable)),
+                Production::NonTerm("STATEMENTIDNEST3"),
             ],
         ),
         (
             ("STATEMENTIDNEST", Type::OpenSqbr),
             vec![
                 Production::Action(create_marker()),
-                Production::NonTerm("INDICE"),
                 Production::NonTerm("REPTIDNEST1"),
                 Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_until_marker(TreeNode::Variable)),
                 Production::NonTerm("STATEMENTIDNEST3"),
             ],
         ),
         (
             ("STATEMENTIDNEST", Type::Assign),
             vec![
-                Production::NonTerm("ASSIGNOP"),
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
                 Production::Action(create_subtree_until_marker(TreeNode::Variable)),
-                Production::NonTerm("EXPR"),
-                Production::Action(create_subtree_from_n_nodes(TreeNode::Assignment, 2)),
+                Production::NonTerm("STATEMENTIDNEST3"),
             ],
         ),
         (
@@ -1505,7 +1611,7 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
                 Production::Term(Type::Dot),
                 Production::Term(Type::Id(String::from(""))),
                 Production::Action(create_leaf()),
-                Production::NonTerm("STATEMENTIDNEST"),
+                Production::NonTerm("STATEMENTIDNEST4"),
             ],
         ),
         (("STATEMENTIDNEST2", Type::Semi), vec![]),
@@ -1515,18 +1621,59 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
                 Production::Term(Type::Dot),
                 Production::Term(Type::Id(String::from(""))),
                 Production::Action(create_leaf()),
-                Production::NonTerm("STATEMENTIDNEST"),
+                Production::NonTerm("STATEMENTIDNEST4"),
             ],
         ),
         (
             ("STATEMENTIDNEST3", Type::Assign),
             vec![
                 Production::NonTerm("ASSIGNOP"),
-                Production::Action(create_subtree_until_marker(TreeNode::Variable)),
                 Production::NonTerm("EXPR"),
                 Production::Action(create_subtree_from_n_nodes(TreeNode::Assignment, 2)),
             ],
         ),
+        (
+            ("STATEMENTIDNEST4", Type::OpenPar),
+            vec![
+                Production::Term(Type::OpenPar),
+                Production::Action(create_marker()),
+                Production::NonTerm("APARAMS"),
+                Production::Action(create_subtree_until_marker(TreeNode::ParameterList)),
+                Production::Term(Type::ClosePar),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::FunctionCall, 3)),
+                Production::NonTerm("STATEMENTIDNEST2"),
+            ],
+        ),
+        (
+            ("STATEMENTIDNEST4", Type::Dot),
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+                Production::NonTerm("STATEMENTIDNEST3"),
+            ],
+        ),
+        (
+            ("STATEMENTIDNEST4", Type::OpenSqbr),
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+                Production::NonTerm("STATEMENTIDNEST3"),
+            ],
+        ),
+        (
+            ("STATEMENTIDNEST4", Type::Assign),
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+                Production::NonTerm("STATEMENTIDNEST3"),
+            ],
+        ),
         (
             ("TERM", Type::OpenPar),
             vec![
@@ -1588,18 +1735,10 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
         (
             ("VARIABLE", Type::Id(String::from(""))),
             vec![
-                Production::Term(Type::Id(String::from(""))),
                 Production::Action(create_marker()),
+                Production::Term(Type::Id(String::from(""))),
                 Production::Action(create_leaf()),
                 Production::NonTerm("VARIABLE2"),
-                Production::Action(create_subtree_until_marker(TreeNode::Variable)),
-            ],
-        ),
-        (
-            ("VARIABLE2", Type::ClosePar),
-            vec![
-                Production::NonTerm("REPTIDNEST1"),
-                Production::NonTerm("REPTVARIABLE"),
             ],
         ),
         (
@@ -1608,18 +1747,29 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
                 Production::Term(Type::OpenPar),
                 Production::Action(create_marker()),
                 Production::NonTerm("APARAMS"),
-                Production::Term(Type::ClosePar),
                 Production::Action(create_subtree_until_marker(TreeNode::ParameterList)),
-                Production::Action(create_subtree_from_n_nodes(TreeNode::FunctionCall, 2)),
+                Production::Term(Type::ClosePar),
+                Production::Action(create_subtree_until_marker(TreeNode::FunctionCall)),
                 Production::NonTerm("VARIDNEST"),
             ],
         ),
+        (
+            ("VARIABLE2", Type::ClosePar),
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_until_marker(TreeNode::Variable)),
+                Production::NonTerm("REPTVARIABLE"),
+            ],
+        ),
         (
             ("VARIABLE2", Type::Dot),
             vec![
                 Production::Action(create_marker()),
                 Production::NonTerm("REPTIDNEST1"),
-                Production::Action(create_subtree_until_marker(TreeNode::IndexedVar)),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_until_marker(TreeNode::Variable)),
                 Production::NonTerm("REPTVARIABLE"),
             ],
         ),
@@ -1628,7 +1778,8 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
             vec![
                 Production::Action(create_marker()),
                 Production::NonTerm("REPTIDNEST1"),
-                Production::Action(create_subtree_until_marker(TreeNode::IndexedVar)),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_until_marker(TreeNode::Variable)),
                 Production::NonTerm("REPTVARIABLE"),
             ],
         ),
@@ -1637,33 +1788,38 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
             vec![
                 Production::Term(Type::Dot),
                 Production::Term(Type::Id(String::from(""))),
-                Production::Action(create_marker()),
                 Production::Action(create_leaf()),
                 Production::NonTerm("VARIDNEST2"),
-                Production::Action(create_subtree_until_marker(TreeNode::NestedVar)),
             ],
         ),
-        (
-            ("VARIDNEST2", Type::ClosePar),
-            vec![Production::NonTerm("REPTIDNEST1")],
-        ),
         (
             ("VARIDNEST2", Type::OpenPar),
             vec![
                 Production::Term(Type::OpenPar),
                 Production::Action(create_marker()),
                 Production::NonTerm("APARAMS"),
-                Production::Term(Type::ClosePar),
                 Production::Action(create_subtree_until_marker(TreeNode::ParameterList)),
+                Production::Term(Type::ClosePar),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::FunctionCall, 3)),
                 Production::NonTerm("VARIDNEST"),
             ],
         ),
+        (
+            ("VARIDNEST2", Type::ClosePar),
+            vec![
+                Production::Action(create_marker()),
+                Production::NonTerm("REPTIDNEST1"),
+                Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
+            ],
+        ),
         (
             ("VARIDNEST2", Type::Dot),
             vec![
                 Production::Action(create_marker()),
                 Production::NonTerm("REPTIDNEST1"),
                 Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
             ],
         ),
         (
@@ -1672,6 +1828,7 @@ pub fn get_parsing_table() -> HashMap<(&'static str, Type), Vec<Production<'stat
                 Production::Action(create_marker()),
                 Production::NonTerm("REPTIDNEST1"),
                 Production::Action(create_subtree_until_marker(TreeNode::IndiceList)),
+                Production::Action(create_subtree_from_n_nodes(TreeNode::NestedVar, 3)),
             ],
         ),
         (
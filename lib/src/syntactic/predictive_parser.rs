@@ -15,7 +15,7 @@ use super::parsing_table::{self, Production};
 type ParserResult = (Vec<String>, Vec<CompilerError>, Vec<CodeNode>);
 
 pub fn parse(scanner: &mut LexerScanner) -> CompilerResult<ParserResult> {
-    let debug = env::var("DEBUG").map_or(false, |_| true);
+    let debug = env::var("DEBUG").is_ok();
     let mut stack: Vec<&Production> = vec![&Production::NonTerm("START")];
     let mut token = match scanner.next_token() {
         Some(t) => t,
@@ -48,11 +48,6 @@ pub fn parse(scanner: &mut LexerScanner) -> CompilerResult<ParserResult> {
             continue;
         };
 
-        // Special-case `self` as a generic ID since the grammar doesn't care
-        if token.token_type == Type::SelfT {
-            token.token_type = Type::Id(String::new());
-        }
-
         if debug {
             eprintln!("token: {:?}", token);
         }
@@ -60,7 +55,7 @@ pub fn parse(scanner: &mut LexerScanner) -> CompilerResult<ParserResult> {
         let top = match stack.last() {
             Some(x) => x,
             None => {
-                return Err(vec![
+                return Err([
                     errors,
                     vec![CompilerError::new(
                         "Parsing stack emptied too early!".to_owned(),
@@ -81,7 +76,7 @@ pub fn parse(scanner: &mut LexerScanner) -> CompilerResult<ParserResult> {
                     eprintln!("t: {:?}", t);
                 }
 
-                if t == &token.token_type {
+                if *t == grammar_type(&token.token_type) {
                     parsed.push(token.lexeme.clone());
                     last_production = top;
                     last_token = token.clone();
@@ -105,10 +100,10 @@ pub fn parse(scanner: &mut LexerScanner) -> CompilerResult<ParserResult> {
             Production::NonTerm(nt) => {
                 if debug {
                     eprintln!("nt: {:?} {:?}", nt, &(nt, token.token_type.clone()));
-                    eprintln!("match: {:?}", &(nt, token.token_type.empty_variant()));
+                    eprintln!("match: {:?}", &(nt, grammar_type(&token.token_type)));
                 }
 
-                match parsing_table.get(&(nt, token.token_type.empty_variant())) {
+                match parsing_table.get(&(nt, grammar_type(&token.token_type))) {
                     Some(productions) => {
                         last_production = top;
                         stack.pop();
@@ -165,7 +160,7 @@ pub fn parse(scanner: &mut LexerScanner) -> CompilerResult<ParserResult> {
     }
 
     if !stack.is_empty() {
-        Err(vec![
+        Err([
             errors,
             vec![CompilerError::new(
                 "Parsing stack not empty at the end!".to_owned(),
@@ -180,6 +175,17 @@ pub fn parse(scanner: &mut LexerScanner) -> CompilerResult<ParserResult> {
 
 type SetTable = HashMap<&'static str, Vec<Type>>;
 
+/// The terminal a token is in the grammar
+///
+/// `self` is parsed like any identifier, but its leaf keeps the `SelfT` token so later passes can
+/// tell it apart from variables.
+fn grammar_type(token_type: &Type) -> Type {
+    match token_type {
+        Type::SelfT => Type::Id(String::new()),
+        _ => token_type.empty_variant(),
+    }
+}
+
 fn skip_error(
     scanner: &mut LexerScanner,
     lookahead: &Token,
@@ -221,7 +227,7 @@ fn skip_error(
         None => panic!("No FOLLOW set found for non-terminal '{}'!", nt),
     };
 
-    let mut top_type = lookahead.token_type.empty_variant();
+    let mut top_type = grammar_type(&lookahead.token_type);
 
     // Pop the stack if the next token is in the FOLLOW set of our current non-terminal on top of the stack.
     if lookahead.token_type == Type::EndOfFile || follow.contains(&top_type) {
@@ -242,7 +248,7 @@ fn skip_error(
             }
         };
 
-        top_type = lookahead.token_type.empty_variant();
+        top_type = grammar_type(&lookahead.token_type);
 
         if first.contains(&top_type) {
             eprintln!("Resuming from token '{}'", lookahead.lexeme);
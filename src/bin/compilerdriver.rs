@@ -1,19 +1,155 @@
 use std::{env, fs, path::Path, process};
 
 use rust_compiler_lib::{
-    ast::nodes::{fmt_symbol_table, string_tree},
-    codegen::codegen_visitor::CodegenVisitor,
-    compiler_error::{errors_to_string, print_errors, CompilerError},
-    lexical::{lexer::LexerScanner, tokens::token_type::Type},
-    semantic::{
-        symbol_collector::SymbolCollectorVisitor, symbol_globals::SymbolGlobalResolverVisitor,
-        symbol_visitor::SymbolTableVisitor, visitor::Visitor,
+    ast::{
+        nodes::string_tree,
+        symbol_table::{fmt_symbol_table, symbol_table_json, SymbolTable},
     },
+    compiler_error::{
+        errors_to_string, print_errors, print_errors_with_source, DiagnosticOptions, WarningKind,
+    },
+    diagnostic_codes,
+    lexical::{
+        lexer::LexerScanner,
+        tokens::{location::Location, token_type::Type},
+    },
+    passes::{
+        builtin::{MOON_CODE, SYMBOL_TABLE, XREF},
+        pass::PassContext,
+        pass_manager::{ErrorPolicy, PassManager},
+    },
+    semantic::xref::XrefIndex,
     syntactic::predictive_parser,
 };
 
+/// Extra outputs written next to the source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Emit {
+    /// Every scope and its symbols as JSON, to `<file>.outsymbols.json`
+    SymbolsJson,
+    /// Where every symbol is defined and used, to `<file>.outxref`
+    Xref,
+}
+
+impl Emit {
+    const ALL: [Emit; 2] = [Emit::SymbolsJson, Emit::Xref];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Emit::SymbolsJson => "symbols-json",
+            Emit::Xref => "xref",
+        }
+    }
+}
+
+/// What to output besides the code, and the errors
+#[derive(Default)]
+struct Outputs {
+    emit: Vec<Emit>,
+    /// Print the symbol at this position
+    symbol_at: Option<Location>,
+}
+
 fn main() -> Result<(), String> {
-    let args: Vec<String> = env::args().collect();
+    // `--emit <output>` is the same as `--emit=<output>`
+    let mut all_args: Vec<String> = vec![];
+    let mut env_args = env::args();
+
+    while let Some(arg) = env_args.next() {
+        if arg == "--emit" {
+            all_args.push(format!("--emit={}", env_args.next().unwrap_or_default()));
+        } else {
+            all_args.push(arg);
+        }
+    }
+
+    let (flags, args): (Vec<String>, Vec<String>) =
+        all_args.into_iter().partition(|a| a.starts_with("--"));
+
+    if let Some(flag) = flags
+        .iter()
+        .find(|f| f.split('=').next() == Some("--explain"))
+    {
+        let code = match flag.split_once('=') {
+            Some((_, code)) => Some(code),
+            None => args.get(1).map(|c| c.as_str()),
+        };
+
+        match code.and_then(diagnostic_codes::lookup) {
+            Some(info) => {
+                println!("{}", info.explain());
+                return Ok(());
+            }
+            None => {
+                eprintln!(
+                    "Unknown diagnostic code '{}', expected one of: {}",
+                    code.unwrap_or_default(),
+                    diagnostic_codes::REGISTRY
+                        .iter()
+                        .map(|i| i.code)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    let mut policy = ErrorPolicy::default();
+    let mut options = DiagnosticOptions::default();
+    let mut outputs = Outputs::default();
+
+    for flag in flags {
+        match flag.split_once('=') {
+            Some(("--on-error", "stop")) => policy = ErrorPolicy::Stop,
+            Some(("--on-error", "continue")) => policy = ErrorPolicy::Continue,
+            Some(("--on-error", "force")) => policy = ErrorPolicy::Force,
+            Some(("--allow", kind)) => match WarningKind::from_name(kind) {
+                Some(kind) => options.allowed.push(kind),
+                None => {
+                    eprintln!(
+                        "Unknown warning kind '{kind}', expected one of: {}",
+                        WarningKind::ALL.map(|k| k.name()).join(", ")
+                    );
+                    process::exit(1);
+                }
+            },
+            Some(("--emit", output)) => match Emit::ALL.into_iter().find(|e| e.name() == output) {
+                Some(output) => outputs.emit.push(output),
+                None => {
+                    eprintln!(
+                        "Unknown output '{output}', expected one of: {}",
+                        Emit::ALL.map(|e| e.name()).join(", ")
+                    );
+                    process::exit(1);
+                }
+            },
+            Some(("--symbol-at", position)) => match position
+                .split_once(':')
+                .and_then(|(line, column)| Some((line.parse().ok()?, column.parse().ok()?)))
+            {
+                Some((line, column)) => outputs.symbol_at = Some(Location::new(line, column)),
+                None => {
+                    eprintln!("Expected a position like '12:7', found '{position}'");
+                    process::exit(1);
+                }
+            },
+            Some(("--max-errors", max)) => match max.parse() {
+                Ok(max) => options.max_errors = Some(max),
+                Err(_) => {
+                    eprintln!("Expected a number of errors, found '{max}'");
+                    process::exit(1);
+                }
+            },
+            None if flag == "--warnings-as-errors" => options.warnings_as_errors = true,
+            _ => {
+                eprintln!("Unknown flag '{flag}'");
+                eprintln!("Usage: compilerdriver [--on-error=stop|continue|force] [--warnings-as-errors] [--allow=<warning kind>]... [--max-errors=<n>] [--emit=<output>]... [--symbol-at=<line>:<column>] <file or directory>");
+                eprintln!("       compilerdriver --explain <code>");
+                process::exit(1);
+            }
+        };
+    }
 
     let filename = match args.get(1) {
         Some(filename) => filename,
@@ -31,7 +167,7 @@ fn main() -> Result<(), String> {
     }
 
     if path.is_file() {
-        return syn_parse_file(path);
+        return syn_parse_file(path, policy, &options, &outputs);
     }
 
     // It's a dir
@@ -40,14 +176,19 @@ fn main() -> Result<(), String> {
             continue;
         }
         println!("Parsing file: {}", entry.path().display());
-        syn_parse_file(entry.path())?;
+        syn_parse_file(entry.path(), policy, &options, &outputs)?;
         println!("\n");
     }
 
     Ok(())
 }
 
-fn syn_parse_file<P>(path: P) -> Result<(), String>
+fn syn_parse_file<P>(
+    path: P,
+    policy: ErrorPolicy,
+    options: &DiagnosticOptions,
+    outputs: &Outputs,
+) -> Result<(), String>
 where
     P: AsRef<Path>,
 {
@@ -118,6 +259,8 @@ where
     let syntax_err_path = path.with_extension("outsyntaxerrors");
     let semantic_tables = path.with_extension("outsymboltables");
     let semantic_err_path = path.with_extension("outsemanticerrors");
+    let symbols_json_path = path.with_extension("outsymbols.json");
+    let xref_path = path.with_extension("outxref");
 
     match predictive_parser::parse(&mut lexer) {
         Ok((derivations, parse_errs, ast_stack)) => {
@@ -140,59 +283,67 @@ where
                 fs::write(ast_path, string_tree(root)).expect("Failed to write to file");
 
                 println!("\nVisiting...\n");
-                let mut visit_errors: Vec<CompilerError> = vec![];
-
-                let mut resolver = SymbolGlobalResolverVisitor::new();
-                let res = resolver.visit(root);
-
-                if let Err(e) = res {
-                    visit_errors.extend(e);
-                }
-
-                let mut visitor = SymbolTableVisitor::new();
-                let res = visitor.visit(root);
-
-                if let Err(e) = res {
-                    visit_errors.extend(e);
+                let mut manager = PassManager::with_default_passes(policy);
+                manager.diagnostics = options.clone();
+                let mut context = PassContext::new_with_errors(parse_errs);
+                manager
+                    .run(root, &mut context)
+                    .map_err(|e| errors_to_string(&e))?;
+
+                if let Some(global) = context.output::<SymbolTable>(SYMBOL_TABLE) {
+                    let tables = format!("Global table:\n{}", fmt_symbol_table(global).unwrap());
+                    println!("{}", tables);
+                    fs::write(semantic_tables, tables).expect("Failed to write to file");
+
+                    if outputs.emit.contains(&Emit::SymbolsJson) {
+                        fs::write(symbols_json_path, symbol_table_json(global))
+                            .expect("Failed to write to file");
+                    }
                 }
 
-                let mut collector = SymbolCollectorVisitor::new();
-                let res = collector.visit(root);
-
-                if let Err(e) = res {
-                    visit_errors.extend(e);
+                if let Some(index) = context.output::<XrefIndex>(XREF) {
+                    if outputs.emit.contains(&Emit::Xref) {
+                        fs::write(xref_path, index.to_string()).expect("Failed to write to file");
+                    }
+
+                    if let Some(location) = &outputs.symbol_at {
+                        match index.symbol_at(location) {
+                            Some(entry) => {
+                                println!("Symbol at {}:{}: {entry}", location.line, location.column)
+                            }
+                            None => {
+                                println!("No symbol at {}:{}", location.line, location.column)
+                            }
+                        }
+                    }
                 }
 
-                let tables = format!(
-                    "Global table:\n{}",
-                    fmt_symbol_table(&collector.global).unwrap()
-                );
-                println!("{}", tables);
-                fs::write(semantic_tables, tables).expect("Failed to write to file");
+                let (diagnostics, hidden) = options.limit(&context.errors);
 
-                if !visit_errors.is_empty() {
-                    fs::write(semantic_err_path, errors_to_string(&visit_errors))
+                if !diagnostics.is_empty() {
+                    fs::write(semantic_err_path, errors_to_string(&diagnostics))
                         .expect("Failed to write to file");
                 }
 
-                let mut codegen_visitor = CodegenVisitor::new();
-                let res = codegen_visitor.visit(root);
+                if let Some(outcode) = context.output::<String>(MOON_CODE) {
+                    let moon_out = path.with_extension("moon");
 
-                if let Err(e) = res {
-                    visit_errors.extend(e);
-                }
-
-                let outcode = codegen_visitor.get_code().trim().to_string();
-                let moon_out = path.with_extension("moon");
-
-                println!("Code generated!");
+                    println!("Code generated!");
 
-                fs::write(moon_out, outcode).expect("Failed to write to file");
+                    fs::write(moon_out, outcode).expect("Failed to write to file");
+                } else if !context.skipped.is_empty() {
+                    println!("Skipped {} due to errors", context.skipped.join(", "));
+                }
 
-                visit_errors.extend(parse_errs);
-                if !visit_errors.is_empty() {
+                if context.has_errors() {
                     eprintln!("Compilation finished with errors:");
-                    print_errors(&visit_errors);
+                } else if !diagnostics.is_empty() {
+                    eprintln!("Compilation finished with warnings:");
+                }
+                print_errors_with_source(&diagnostics, &content);
+
+                if hidden > 0 {
+                    eprintln!("... and {hidden} more errors");
                 }
             } else {
                 println!("No AST generated!");
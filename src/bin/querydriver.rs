@@ -0,0 +1,95 @@
+use std::{env, fs, path::Path, process};
+
+use rust_compiler_lib::{
+    ast::query::Query, compiler_error::print_errors, lexical::lexer::LexerScanner,
+    syntactic::predictive_parser,
+};
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let (query, filename) = match (args.get(1), args.get(2)) {
+        (Some(query), Some(filename)) => (query, filename),
+        _ => {
+            eprintln!("Usage: querydriver <query> <file or directory>");
+            eprintln!("  ie. querydriver 'WhileBlock FunctionCall#add' ./test");
+            process::exit(1);
+        }
+    };
+
+    let query: Query = match query.parse() {
+        Ok(query) => query,
+        Err(err) => {
+            eprintln!("Invalid query: {}", err);
+            process::exit(1);
+        }
+    };
+
+    let path = Path::new(&filename);
+
+    if !path.exists() {
+        eprintln!("File or directory does not exist");
+        process::exit(1);
+    }
+
+    if path.is_file() {
+        query_file(&query, path);
+        return;
+    }
+
+    // It's a dir
+    for entry in path.read_dir().expect("Failed to read directory").flatten() {
+        if entry.metadata().unwrap().is_dir() || entry.path().extension().unwrap() != "src" {
+            continue;
+        }
+        query_file(&query, entry.path());
+    }
+}
+
+fn query_file<P>(query: &Query, path: P)
+where
+    P: AsRef<Path>,
+{
+    let path: &Path = path.as_ref();
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Error while reading file: {}", err);
+            process::exit(1);
+        }
+    };
+
+    let mut lexer = LexerScanner::new(&content);
+
+    let root = match predictive_parser::parse(&mut lexer) {
+        Ok((_, _, ast_stack)) => match ast_stack.first() {
+            Some(root) => root.clone(),
+            None => {
+                eprintln!("{}: No AST generated!", path.display());
+                return;
+            }
+        },
+        Err(errs) => {
+            eprintln!("{}: Parsing failed:", path.display());
+            print_errors(&errs);
+            return;
+        }
+    };
+
+    let lines: Vec<&str> = content.lines().collect();
+
+    for found in query.find(&root) {
+        let line = lines
+            .get(found.span.start.line.saturating_sub(1))
+            .map_or("", |l| l.trim());
+
+        println!(
+            "{}:{}:{}: {} | {}",
+            path.display(),
+            found.span.start.line,
+            found.span.start.column,
+            found.node.borrow().value,
+            line
+        );
+    }
+}
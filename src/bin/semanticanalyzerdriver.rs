@@ -1,12 +1,16 @@
 use std::{env, fs, path::Path, process};
 
 use rust_compiler_lib::{
-    ast::nodes::{fmt_symbol_table, string_tree},
-    compiler_error::{errors_to_string, print_errors, CompilerError},
+    ast::{
+        nodes::string_tree,
+        symbol_table::{fmt_symbol_table, SymbolTable},
+    },
+    compiler_error::{errors_to_string, print_errors, print_errors_with_source},
     lexical::lexer::LexerScanner,
-    semantic::{
-        symbol_collector::SymbolCollectorVisitor, symbol_visitor::SymbolTableVisitor,
-        visitor::Visitor,
+    passes::{
+        builtin::SYMBOL_TABLE,
+        pass::PassContext,
+        pass_manager::{ErrorPolicy, PassManager},
     },
     syntactic::predictive_parser,
 };
@@ -88,36 +92,28 @@ where
                 fs::write(ast_path, string_tree(root)).expect("Failed to write to file");
 
                 println!("\nVisiting...\n");
-                let mut visitor = SymbolTableVisitor::new();
-                let res = visitor.visit(root);
-                let mut visit_errors: Vec<CompilerError> = parse_errs;
-
-                if let Err(e) = res {
-                    visit_errors.extend(e);
+                let manager = PassManager::with_semantic_passes(ErrorPolicy::Continue);
+                let mut context = PassContext::new_with_errors(parse_errs);
+                manager
+                    .run(root, &mut context)
+                    .map_err(|e| errors_to_string(&e))?;
+
+                if let Some(global) = context.output::<SymbolTable>(SYMBOL_TABLE) {
+                    let tables = format!("Global table:\n{}", fmt_symbol_table(global).unwrap());
+                    println!("{}", tables);
+                    fs::write(semantic_tables, tables).expect("Failed to write to file");
                 }
 
-                let mut collector = SymbolCollectorVisitor::new();
-                let res = collector.visit(root);
-
-                if let Err(e) = res {
-                    visit_errors.extend(e);
-                }
-
-                let tables = format!(
-                    "Global table:\n{}",
-                    fmt_symbol_table(&collector.global).unwrap()
-                );
-                println!("{}", tables);
-                fs::write(semantic_tables, tables).expect("Failed to write to file");
-
-                if !visit_errors.is_empty() {
-                    fs::write(semantic_err_path, errors_to_string(&visit_errors))
+                if !context.errors.is_empty() {
+                    fs::write(semantic_err_path, errors_to_string(&context.errors))
                         .expect("Failed to write to file");
-                }
 
-                if !visit_errors.is_empty() {
-                    eprintln!("Compilation finished with errors:");
-                    print_errors(&visit_errors);
+                    if context.has_errors() {
+                        eprintln!("Compilation finished with errors:");
+                    } else {
+                        eprintln!("Compilation finished with warnings:");
+                    }
+                    print_errors_with_source(&context.errors, &content);
                 }
             } else {
                 println!("No AST generated!");